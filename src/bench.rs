@@ -0,0 +1,89 @@
+// Imports
+
+use crate::board::Game;
+use crate::moves;
+use crate::move_input;
+use crate::engine::{ self, MoveOrdering };
+use crate::engine::book::Book;
+
+use std::hint;
+use std::time::{ Duration, Instant };
+
+// Benchmark positions as text grids with perft depth
+
+const POSITIONS: [(&str, &str, u32); 2] = [
+    (
+        "start position",
+        "rnbqkbnr\npppppppp\n........\n........\n........\n........\nPPPPPPPP\nRNBQKBNR\nw",
+        5
+    ),
+    (
+        "tactical position",
+        "r...k..r\np.ppqpb.\nbn..pnp.\n...PN...\n.p..P...\n..N..Q.p\nPPPBBPPP\nR...K..R\nw",
+        4
+    )
+];
+const ITERATIONS: u32 = 10000;
+const SEARCH_DEPTH: u32 = 4;
+const ORDERING_DEPTH: u32 = 2;
+
+// Run move generation benchmarks and print results
+
+pub fn run_benchmark() {
+    for (name, position, depth) in POSITIONS {
+        let mut game = Game::from_board_str(position).expect("Invalid benchmark position");
+        println!("Benchmark {name}:");
+
+        // Measure perft nodes per second
+
+        let start = Instant::now();
+        let nodes = game.perft(depth);
+        print_rate(&format!("perft {depth}"), nodes, "nodes", start.elapsed());
+
+        // Measure legal move generation
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            hint::black_box(game.generate_legal_moves());
+        }
+        print_rate("legal move generation", ITERATIONS as u64, "calls", start.elapsed());
+
+        // Measure in check detection
+
+        let king_pos = game.king_positions.get_pos(game.turn);
+        let start = Instant::now();
+        for _ in 0..ITERATIONS * 10 {
+            hint::black_box(moves::in_check(game.turn, hint::black_box(king_pos), &game.board));
+        }
+        print_rate("in check detection", ITERATIONS as u64 * 10, "calls", start.elapsed());
+
+        // Measure engine search time
+
+        let mut rng = engine::create_rng(engine::DEFAULT_SEED);
+        let start = Instant::now();
+        let best = engine::best_move(&mut game, &Book::empty(), SEARCH_DEPTH, &mut rng);
+        print_rate(&format!("search depth {SEARCH_DEPTH}"), 1, "searches", start.elapsed());
+        if let Some((mov, promotion)) = best {
+            println!("  best move: {}", move_input::to_coordinates(mov, promotion));
+        }
+
+        // Compare search nodes by move ordering
+
+        for ordering in [MoveOrdering::Unordered, MoveOrdering::Captures, MoveOrdering::Full] {
+            let nodes = engine::count_search_nodes(&mut game, ORDERING_DEPTH, ordering);
+            println!("  {ordering:?} ordering: {nodes} nodes at depth {ORDERING_DEPTH}");
+        }
+        println!();
+    }
+}
+
+// Print count and rate per second
+
+fn print_rate(label: &str, count: u64, unit: &str, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    println!(
+        "  {label}: {count} {unit} in {:.3}s ({:.0} {unit}/s)",
+        seconds,
+        count as f64 / seconds.max(f64::EPSILON)
+    );
+}
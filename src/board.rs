@@ -1,8 +1,13 @@
 // Imports
 
+use std::fmt;
+use std::str::FromStr;
 use std::ops::Range;
 
 use crate::zobrist::ZobristTable;
+use crate::game::DrawType;
+use crate::clock::Clock;
+use crate::moves;
 
 // Game parameters
 
@@ -24,22 +29,36 @@ pub struct CastledPositions {
 
 pub struct CastleColumns(pub Range<usize>, pub Range<usize>);
 
+// Board size used for rows and columns, with pawn, promotion, king, rook, and castle squares derived from it
+// The board-9x9 feature plays on a 9x9 board with a second queen and skips tests written for 8x8 positions
+
+#[cfg(not(feature = "board-9x9"))]
 pub const SIZE: usize = 8;
+#[cfg(feature = "board-9x9")]
+pub const SIZE: usize = 9;
 pub const PIECE_TYPES: usize = 12;
 pub const PROMOTION_PIECES: [PieceType; 4] = [Knight, Bishop, Rook, Queen];
+#[cfg(not(feature = "board-9x9"))]
+pub const BACK_RANK: [PieceType; SIZE] = [Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook];
+#[cfg(feature = "board-9x9")]
+pub const BACK_RANK: [PieceType; SIZE] = [Rook, Knight, Bishop, Queen, King, Queen, Bishop, Knight, Rook];
+const KING_COLUMN: usize = 4;                               // King start column, castling moves king two columns
+pub const PAWN_START_ROWS: (usize, usize) = (1, SIZE - 2); // White and black pawn start rows
+pub const PROMOTION_ROWS: (usize, usize) = (SIZE - 1, 0);   // White and black promotion rows
 pub const INITIAL_POSITIONS: Positions = Positions {
-    WHITE_KING: Point(0, 4),
-    BLACK_KING: Point(7, 4),
-    WHITE_ROOKS: (Point(0, 0), Point(0, 7)),
-    BLACK_ROOKS: (Point(7, 0), Point(7, 7))
+    WHITE_KING: Point(0, KING_COLUMN),
+    BLACK_KING: Point(SIZE - 1, KING_COLUMN),
+    WHITE_ROOKS: (Point(0, 0), Point(0, SIZE - 1)),
+    BLACK_ROOKS: (Point(SIZE - 1, 0), Point(SIZE - 1, SIZE - 1))
 };
 pub const CASTLED_POSITIONS: CastledPositions = CastledPositions {
-    WHITE_KING: (Point(0, 2), Point(0, 6)),
-    BLACK_KING: (Point(7, 2), Point(7, 6)),
-    WHITE_ROOKS: (Point(0, 3), Point(0, 5)),
-    BLACK_ROOKS: (Point(7, 3), Point(7, 5))
+    WHITE_KING: (Point(0, KING_COLUMN - 2), Point(0, KING_COLUMN + 2)),
+    BLACK_KING: (Point(SIZE - 1, KING_COLUMN - 2), Point(SIZE - 1, KING_COLUMN + 2)),
+    WHITE_ROOKS: (Point(0, KING_COLUMN - 1), Point(0, KING_COLUMN + 1)),
+    BLACK_ROOKS: (Point(SIZE - 1, KING_COLUMN - 1), Point(SIZE - 1, KING_COLUMN + 1))
 };
-pub const CASTLE_COLUMNS: CastleColumns = CastleColumns(2..5, 4..7);
+pub const CASTLE_COLUMNS: CastleColumns = CastleColumns(KING_COLUMN - 2..KING_COLUMN + 1, KING_COLUMN..KING_COLUMN + 3); // Columns king crosses that can't be attacked
+pub const CASTLE_EMPTY_COLUMNS: CastleColumns = CastleColumns(1..KING_COLUMN, KING_COLUMN + 1..SIZE - 1);             // Columns between king and rook that must be empty
 
 // Player side
 
@@ -61,6 +80,40 @@ impl Side {
             Black => White
         }
     }
+
+    // Get side from FEN color character
+
+    pub fn from_char(char: char) -> Option<Side> {
+        match char {
+            'w' => Some(White),
+            'b' => Some(Black),
+            _ => None
+        }
+    }
+
+    // Get FEN color character for side
+
+    pub fn to_char(&self) -> char {
+        match self {
+            White => 'w',
+            Black => 'b'
+        }
+    }
+
+    // Get capitalized side name for display
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            White => "White",
+            Black => "Black"
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
 }
 
 // Square on chess board
@@ -76,6 +129,30 @@ pub enum Square {
 #[derive(Debug)]
 pub struct Point(pub usize, pub usize);
 
+// Display point as algebraic square name like e4
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.1 as u8) as char, self.0 + 1)
+    }
+}
+
+// Parse point from algebraic square name like e4
+
+impl FromStr for Point {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Point, String> {
+        let chars: Vec<char> = input.chars().collect();
+        let col = chars.first().and_then(|col| (*col as usize).checked_sub('a' as usize));
+        let row = chars.get(1).and_then(|row| row.to_digit(10)).and_then(|row| (row as usize).checked_sub(1));
+        match (col, row) {
+            (Some(col), Some(row)) if chars.len() == 2 && row < SIZE && col < SIZE => Ok(Point(row, col)),
+            _ => Err(format!("'{input}' isn't a valid square"))
+        }
+    }
+}
+
 impl Square {
     // Get full square with piece
 
@@ -89,10 +166,7 @@ impl Square {
 
     pub fn get_char(&self) -> &'static str {
         match self {
-            Square::Full(piece) => match piece.side {
-                White => piece.get_char(),
-                Black => piece.get_char()
-            },
+            Square::Full(piece) => piece.get_char(),
             Square::Empty => " "
         }
     }
@@ -121,16 +195,35 @@ pub enum PieceType {
 use PieceType::{ Pawn, Knight, Bishop, Rook, Queen, King };
 
 impl Piece {
-    // Get character for piece
+    // Get character for piece with outline glyphs for white and filled glyphs for black
 
     pub fn get_char(&self) -> &'static str {
-        match self.typ {
-            Pawn => "♟︎",
-            Knight => "♞",
-            Bishop => "♝",
-            Rook => "♜",
-            Queen => "♛",
-            King => "♚"
+        match self.side {
+            White => match self.typ {
+                Pawn => "♙",
+                Knight => "♘",
+                Bishop => "♗",
+                Rook => "♖",
+                Queen => "♕",
+                King => "♔"
+            },
+            Black => match self.typ {
+                Pawn => "♟︎",
+                Knight => "♞",
+                Bishop => "♝",
+                Rook => "♜",
+                Queen => "♛",
+                King => "♚"
+            }
+        }
+    }
+
+    // Get FEN letter for piece with uppercase for white and lowercase for black
+
+    pub fn get_letter(&self) -> char {
+        match self.side {
+            White => self.typ.get_letter(),
+            Black => self.typ.get_letter().to_ascii_lowercase()
         }
     }
 }
@@ -150,6 +243,32 @@ impl PieceType {
         }
     }
 
+    // Get character for piece type
+
+    pub fn get_letter(&self) -> char {
+        match self {
+            Pawn => 'P',
+            Knight => 'N',
+            Bishop => 'B',
+            Rook => 'R',
+            Queen => 'Q',
+            King => 'K'
+        }
+    }
+
+    // Get material value in centipawns with finite sentinel for king
+
+    pub fn value(&self) -> i32 {
+        match self {
+            Pawn => 100,
+            Knight => 320,
+            Bishop => 330,
+            Rook => 500,
+            Queen => 900,
+            King => 20000
+        }
+    }
+
     // Get piece name for piece type
 
     pub fn get_name(&self) -> &'static str {
@@ -166,15 +285,24 @@ impl PieceType {
 
 // Move between two squares
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub struct Move {
     pub from: Point,
     pub to: Point
 }
 
+// Display move in coordinate notation like e2e4 without promotion piece
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)
+    }
+}
+
 // King coordinates on board for black and white
 
+#[derive(Clone, Copy)]
 #[derive(Debug)]
 pub struct KingPositions {
     pub white: Point,
@@ -201,7 +329,7 @@ pub struct CastleRights {
     pub black: (bool, bool)
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub enum CastleDirection {
     A,
@@ -209,6 +337,24 @@ pub enum CastleDirection {
 }
 
 impl CastleRights {
+    // Get castle rights for kings and rooks on initial squares
+
+    pub fn from_board(board: &[[Square; SIZE]; SIZE]) -> CastleRights {
+        let on_square = |point: Point, side: Side, typ: PieceType| board[point.0][point.1] == Square::full(side, typ);
+        let white_king = on_square(INITIAL_POSITIONS.WHITE_KING, White, King);
+        let black_king = on_square(INITIAL_POSITIONS.BLACK_KING, Black, King);
+        CastleRights {
+            white: (
+                white_king && on_square(INITIAL_POSITIONS.WHITE_ROOKS.0, White, Rook),
+                white_king && on_square(INITIAL_POSITIONS.WHITE_ROOKS.1, White, Rook)
+            ),
+            black: (
+                black_king && on_square(INITIAL_POSITIONS.BLACK_ROOKS.0, Black, Rook),
+                black_king && on_square(INITIAL_POSITIONS.BLACK_ROOKS.1, Black, Rook)
+            )
+        }
+    }
+
     // Get castle right for side and direction
 
     pub fn has_right(&self, side: Side, dir: CastleDirection) -> bool {
@@ -225,8 +371,26 @@ impl CastleRights {
     }
 }
 
+// Move made in game with previous state for undoing
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct MoveRecord {
+    pub mov: Move,
+    pub promotion: Option<PieceType>,
+    pub board: [[Square; SIZE]; SIZE],
+    pub king_positions: KingPositions,
+    pub castle_rights: CastleRights,
+    pub pawn_double_moved: Option<Point>,
+    pub last_active_ply: u32,
+    pub captured: Option<Piece>
+}
+
 // Chess game state
+// Clones copy the move history and repetition counts, so moves made on a
+// clone (such as an analysis copy) never change counts in the original game
 
+#[derive(Clone)]
 #[derive(Debug)]
 pub struct Game {
     pub turn: Side,                       // Current active player up to move
@@ -235,63 +399,39 @@ pub struct Game {
     pub castle_rights: CastleRights,      // Option to castle for both sides
     pub pawn_double_moved: Option<Point>, // Track last double pawn move for en passant
     pub last_active_ply: u32,             // Track distance from last active ply for 50 move rule
-    pub zobrist_table: ZobristTable<u32>
+    pub fullmove: u32,                    // Current move number starting at 1
+    pub start_fullmove: u32,              // Move number of starting position for PGN numbering
+    pub start_turn: Side,                 // Side to move in starting position
+    pub zobrist_table: ZobristTable<u32>, // Count occurrences of each position for repetition
+    pub history: Vec<MoveRecord>,         // Moves played with previous state for undo
+    #[allow(clippy::type_complexity)]
+    pub legal_cache: Option<(u64, Vec<(Move, Option<PieceType>)>)>, // Legal moves for position key
+    pub claimed_draw: Option<DrawType>,   // Draw claimed by player to move
+    pub automatic_threefold: bool,        // End game on threefold instead of fivefold repetition
+    pub fifty_move_rule: bool,            // Draw games by the fifty move rule
+    pub repetition_rule: bool,            // Draw games by automatic or claimed repetition
+    pub clock: Option<Clock>,             // Remaining time for timed games
+    pub flagged: Option<Side>,            // Side that ran out of time
+    pub quit: bool                        // Player quit game to return to menu
 }
 
 impl Game {
     // Create new game in starting position
 
     pub fn new() -> Game {
-        let board = [
-            [
-                Square::full(White, Rook),
-                Square::full(White, Knight),
-                Square::full(White, Bishop),
-                Square::full(White, Queen),
-                Square::full(White, King),
-                Square::full(White, Bishop),
-                Square::full(White, Knight),
-                Square::full(White, Rook)
-            ],
-            [
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn),
-                Square::full(White, Pawn)
-            ],
-            [Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty],
-            [Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty],
-            [Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty],
-            [Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty, Square::Empty],
-            [
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn),
-                Square::full(Black, Pawn)
-            ],
-            [
-                Square::full(Black, Rook),
-                Square::full(Black, Knight),
-                Square::full(Black, Bishop),
-                Square::full(Black, Queen),
-                Square::full(Black, King),
-                Square::full(Black, Bishop),
-                Square::full(Black, Knight),
-                Square::full(Black, Rook)
-            ]
-        ];
+        let mut board = [[Square::Empty; SIZE]; SIZE];
+        for (col, typ) in BACK_RANK.into_iter().enumerate() {
+            board[0][col] = Square::full(White, typ);
+            board[PAWN_START_ROWS.0][col] = Square::full(White, Pawn);
+            board[PAWN_START_ROWS.1][col] = Square::full(Black, Pawn);
+            board[SIZE - 1][col] = Square::full(Black, typ);
+        }
         let castle_rights = CastleRights {
             white: (true, true),
             black: (true, true)
         };
+        let mut zobrist_table = ZobristTable::new(16, White, &board, castle_rights, None, None);
+        zobrist_table.insert(1);
 
         Game {
             turn: White,
@@ -303,7 +443,528 @@ impl Game {
             castle_rights,
             pawn_double_moved: None,
             last_active_ply: 0,
-            zobrist_table: ZobristTable::new(16, White, &board, castle_rights, None)
+            fullmove: 1,
+            start_fullmove: 1,
+            start_turn: White,
+            zobrist_table,
+            history: vec![],
+            legal_cache: None,
+            claimed_draw: None,
+            automatic_threefold: false,
+            fifty_move_rule: true,
+            repetition_rule: true,
+            clock: None,
+            flagged: None,
+            quit: false
+        }
+    }
+
+    // Create game from board position and state
+
+    pub fn from_position(
+        turn: Side,
+        board: [[Square; SIZE]; SIZE],
+        castle_rights: CastleRights,
+        pawn_double_moved: Option<Point>,
+        last_active_ply: u32,
+        fullmove: u32
+    ) -> Result<Game, String> {
+        // Find single king for each side
+
+        let mut white_kings = vec![];
+        let mut black_kings = vec![];
+        for (r, row) in board.iter().enumerate() {
+            for (c, square) in row.iter().enumerate() {
+                if let Square::Full(Piece { side, typ: King }) = square {
+                    match side {
+                        White => white_kings.push(Point(r, c)),
+                        Black => black_kings.push(Point(r, c))
+                    }
+                }
+            }
+        }
+        if white_kings.len() != 1 || black_kings.len() != 1 {
+            return Err(String::from("Position must have exactly one king for each side"));
+        }
+
+        // Check for pawns on first or last rank
+
+        for row in [0, SIZE - 1] {
+            if board[row].iter().any(|square| matches!(square, Square::Full(Piece { typ: Pawn, .. }))) {
+                return Err(format!("Pawns can't be on rank {}", row + 1));
+            }
+        }
+
+        // Check kings aren't adjacent by Chebyshev distance
+
+        let (white_king, black_king) = (white_kings[0], black_kings[0]);
+        if white_king.0.abs_diff(black_king.0).max(white_king.1.abs_diff(black_king.1)) <= 1 {
+            return Err(String::from("Kings can't be on adjacent squares"));
+        }
+
+        // Check side not to move isn't in check
+
+        let in_check = match turn {
+            White => moves::in_check(Black, black_king, &board),
+            Black => moves::in_check(White, white_king, &board)
+        };
+        if in_check {
+            return Err(String::from("Side not to move can't be in check"));
+        }
+
+        // Initialize game with position counted once so repeating it twice is threefold
+
+        let mut zobrist_table = ZobristTable::new(16, turn, &board, castle_rights, pawn_double_moved, None);
+        zobrist_table.insert(1);
+
+        Ok(Game {
+            turn,
+            board,
+            king_positions: KingPositions {
+                white: white_king,
+                black: black_king
+            },
+            castle_rights,
+            pawn_double_moved,
+            last_active_ply,
+            fullmove,
+            start_fullmove: fullmove,
+            start_turn: turn,
+            zobrist_table,
+            history: vec![],
+            legal_cache: None,
+            claimed_draw: None,
+            automatic_threefold: false,
+            fifty_move_rule: true,
+            repetition_rule: true,
+            clock: None,
+            flagged: None,
+            quit: false
+        })
+    }
+
+    // Create game from Forsyth-Edwards Notation string
+
+    pub fn from_fen(fen: &str) -> Result<Game, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(format!("'{fen}' isn't a valid FEN string"));
+        }
+
+        // Parse piece placement from rank 8 to rank 1
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != SIZE {
+            return Err(format!("FEN must have {SIZE} ranks"));
+        }
+
+        let mut board = [[Square::Empty; SIZE]; SIZE];
+        for (i, rank) in ranks.iter().enumerate() {
+            let row = SIZE - 1 - i;
+            let mut col = 0;
+            for char in rank.chars() {
+                if let Some(empty) = char.to_digit(10) {
+                    col += empty as usize;
+                    continue;
+                }
+                if col >= SIZE {
+                    return Err(format!("Rank {} must have exactly {SIZE} squares", SIZE - i));
+                }
+                let side = if char.is_ascii_uppercase() { White } else { Black };
+                match PieceType::get_type(char.to_ascii_uppercase()) {
+                    Some(typ) => board[row][col] = Square::full(side, typ),
+                    None => return Err(format!("'{char}' isn't a valid piece on rank {}", SIZE - i))
+                }
+                col += 1;
+            }
+            if col != SIZE {
+                return Err(format!("Rank {} must have exactly {SIZE} squares", SIZE - i));
+            }
+        }
+
+        // Parse side to move and castle rights
+
+        let mut side = fields[1].chars();
+        let turn = match (side.next().and_then(Side::from_char), side.next()) {
+            (Some(turn), None) => turn,
+            _ => return Err(format!("'{}' isn't a valid side to move", fields[1]))
+        };
+
+        if fields[2] != "-" && !fields[2].chars().all(|char| "KQkq".contains(char)) {
+            return Err(format!("'{}' isn't a valid castling field", fields[2]));
+        }
+        let castle_rights = CastleRights {
+            white: (fields[2].contains('Q'), fields[2].contains('K')),
+            black: (fields[2].contains('q'), fields[2].contains('k'))
+        };
+
+        // Parse en passant target square behind double moved pawn with empty squares it passed over
+
+        let pawn_double_moved = match fields[3] {
+            "-" => None,
+            square => {
+                let (target_row, pawn_row, origin_row) = match turn {
+                    White => (PAWN_START_ROWS.1 - 1, PAWN_START_ROWS.1 - 2, PAWN_START_ROWS.1),
+                    Black => (PAWN_START_ROWS.0 + 1, PAWN_START_ROWS.0 + 2, PAWN_START_ROWS.0)
+                };
+                let col = match square.parse::<Point>() {
+                    Ok(Point(row, col)) if row == target_row => col,
+                    _ => return Err(format!("'{square}' isn't a valid en passant square"))
+                };
+                let passed_empty = board[target_row][col] == Square::Empty && board[origin_row][col] == Square::Empty;
+                if board[pawn_row][col] != Square::full(turn.get_opposite(), Pawn) || !passed_empty {
+                    return Err(format!("'{square}' isn't behind a pawn that just moved two squares"));
+                }
+                Some(Point(pawn_row, col))
+            }
+        };
+
+        // Parse halfmove clock and fullmove number
+
+        let last_active_ply = match fields.get(4) {
+            Some(field) => field.parse().map_err(|_| format!("'{field}' isn't a valid halfmove clock"))?,
+            None => 0
+        };
+        let fullmove = match fields.get(5) {
+            Some(field) => match field.parse() {
+                Ok(fullmove) if fullmove > 0 => fullmove,
+                _ => return Err(format!("'{field}' isn't a valid fullmove number"))
+            },
+            None => 1
+        };
+
+        Game::from_position(turn, board, castle_rights, pawn_double_moved, last_active_ply, fullmove)
+    }
+
+    // Get square passed over by last double pawn move whether or not it can be captured
+
+    pub fn en_passant_square(&self) -> Option<Point> {
+        self.pawn_double_moved.map(|Point(row, col)| match self.turn {
+            White => Point(row + 1, col),
+            Black => Point(row - 1, col)
+        })
+    }
+
+    // Get Forsyth-Edwards Notation string for position
+
+    pub fn to_fen(&self) -> String {
+        // Write piece placement from rank 8 to rank 1 with runs of empty squares as digits
+
+        let mut ranks = vec![];
+        for row in self.board.iter().rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for square in row {
+                match square {
+                    Square::Full(piece) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(piece.get_letter());
+                    },
+                    Square::Empty => empty += 1
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+
+        // Write castle rights and en passant target square behind double moved pawn
+
+        let mut castling = String::new();
+        for (right, char) in [
+            (self.castle_rights.white.1, 'K'),
+            (self.castle_rights.white.0, 'Q'),
+            (self.castle_rights.black.1, 'k'),
+            (self.castle_rights.black.0, 'q')
+        ] {
+            if right {
+                castling.push(char);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant_square() {
+            Some(point) => point.to_string(),
+            None => String::from("-")
+        };
+
+        format!(
+            "{} {} {castling} {en_passant} {} {}",
+            ranks.join("/"),
+            self.turn.to_char(),
+            self.last_active_ply,
+            self.fullmove
+        )
+    }
+
+    // Create game from text grid of ranks 8 to 1 and side to move
+
+    pub fn from_board_str(s: &str) -> Result<Game, String> {
+        // Split rank lines and side to move line
+
+        let mut lines: Vec<&str> = s.lines().map(|line| line.trim_end_matches('\r')).collect();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        if lines.len() != SIZE + 1 {
+            return Err(format!("Board must have {SIZE} ranks followed by the side to move"));
+        }
+
+        // Parse pieces in each rank
+
+        let mut board = [[Square::Empty; SIZE]; SIZE];
+        for (i, line) in lines[..SIZE].iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.len() != SIZE {
+                return Err(format!("Rank {} must have exactly {SIZE} squares", SIZE - i));
+            }
+
+            let row = SIZE - 1 - i;
+            for (col, char) in chars.into_iter().enumerate() {
+                if char == '.' || char == ' ' {
+                    continue;
+                }
+                let side = if char.is_ascii_uppercase() { White } else { Black };
+                match PieceType::get_type(char.to_ascii_uppercase()) {
+                    Some(typ) => board[row][col] = Square::full(side, typ),
+                    None => return Err(format!("'{char}' isn't a valid piece on rank {}", SIZE - i))
+                }
+            }
+        }
+
+        // Parse side to move
+
+        let turn = match lines[SIZE].trim().to_lowercase().as_str() {
+            "w" | "white" => White,
+            "b" | "black" => Black,
+            side => return Err(format!("'{side}' isn't a valid side to move"))
+        };
+
+        // Give castle rights for kings and rooks on initial squares
+
+        Game::from_position(turn, board, CastleRights::from_board(&board), None, 0, 1)
+    }
+
+    // Place piece on square, moving king of same side if placing king
+
+    pub fn set_piece(&mut self, point: Point, piece: Piece) -> Result<(), String> {
+        let mut board = self.board;
+        if piece.typ == King {
+            let king_pos = self.king_positions.get_pos(piece.side);
+            board[king_pos.0][king_pos.1] = Square::Empty;
+        } else if let Square::Full(Piece { typ: King, .. }) = board[point.0][point.1] {
+            return Err(String::from("Can't replace a king, move it to another square first"));
+        }
+        board[point.0][point.1] = Square::Full(piece);
+
+        self.set_board(board)
+    }
+
+    // Remove piece from square
+
+    pub fn clear_piece(&mut self, point: Point) -> Result<(), String> {
+        let mut board = self.board;
+        if let Square::Full(Piece { typ: King, .. }) = board[point.0][point.1] {
+            return Err(String::from("Can't remove a king, move it to another square instead"));
+        }
+        board[point.0][point.1] = Square::Empty;
+
+        self.set_board(board)
+    }
+
+    // Set side to move, checking the side not to move isn't in check
+
+    pub fn set_turn(&mut self, turn: Side) -> Result<(), String> {
+        let game = Game::from_position(turn, self.board, self.castle_rights, None, self.last_active_ply, self.fullmove)?;
+        self.replace_position(game);
+        Ok(())
+    }
+
+    // Set castle rights, keeping only rights for kings and rooks on initial squares
+
+    pub fn set_castle_rights(&mut self, castle_rights: CastleRights) -> Result<(), String> {
+        self.castle_rights = castle_rights;
+        self.set_board(self.board)
+    }
+
+    // Replace board and recompute king positions, Zobrist key, and castle rights
+    // History and repetition counts restart from the edited position since earlier
+    // moves can't be undone or replayed from it, while game settings and clock are kept
+
+    fn set_board(&mut self, board: [[Square; SIZE]; SIZE]) -> Result<(), String> {
+        // Keep castle rights only for kings and rooks still on initial squares
+
+        let available = CastleRights::from_board(&board);
+        let castle_rights = CastleRights {
+            white: (self.castle_rights.white.0 && available.white.0, self.castle_rights.white.1 && available.white.1),
+            black: (self.castle_rights.black.0 && available.black.0, self.castle_rights.black.1 && available.black.1)
+        };
+
+        let game = Game::from_position(self.turn, board, castle_rights, None, self.last_active_ply, self.fullmove)?;
+        self.replace_position(game);
+        Ok(())
+    }
+
+    // Replace position state with edited game while keeping game settings and clock
+
+    fn replace_position(&mut self, game: Game) {
+        *self = Game {
+            automatic_threefold: self.automatic_threefold,
+            fifty_move_rule: self.fifty_move_rule,
+            repetition_rule: self.repetition_rule,
+            clock: self.clock,
+            ..game
+        };
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+
+    // Malformed FEN strings are rejected with an error
+
+    #[test]
+    fn from_fen_malformed() {
+        let invalid = [
+            "rnbqkbnrr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/4x3/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+            "4k3/8/8/3Pn3/8/8/8/4K3 w - e6 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w",
+            "8/8/8/8/8/8/8/8 w - - 0 1"
+        ];
+        for fen in invalid {
+            assert!(Game::from_fen(fen).is_err(), "{fen}");
+        }
+        assert_eq!(
+            Game::from_fen(invalid[0]).err(),
+            Some(String::from("Rank 8 must have exactly 8 squares"))
+        );
+    }
+
+    // Valid FEN strings round trip through to_fen
+
+    #[test]
+    fn from_fen_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"
+        ];
+        for fen in fens {
+            assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    // Pawns on the back ranks are rejected and pawns next to them generate moves without panicking
+
+    #[test]
+    fn pawns_near_edge() {
+        assert!(Game::from_fen("P3k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+        assert!(Game::from_fen("4k3/8/8/8/8/8/8/p3K3 b - - 0 1").is_err());
+
+        let mut game = Game::from_fen("4k3/P6P/8/8/8/8/p6p/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.perft(1), 13);
+        assert!(game.perft(3) > 0);
+    }
+
+    // Editing pieces keeps derived state in sync and keeps game settings
+
+    #[test]
+    fn edit_position() {
+        let mut game = Game::new();
+        game.automatic_threefold = true;
+        game.fifty_move_rule = false;
+        game.repetition_rule = false;
+
+        game.clear_piece(Point(0, 0)).unwrap();
+        assert_eq!(game.castle_rights.white, (false, true));
+        game.set_piece(Point(3, 3), Piece { side: White, typ: King }).unwrap();
+        assert_eq!(game.king_positions.white, Point(3, 3));
+        assert_eq!(game.board[0][4], Square::Empty);
+        assert_eq!(game.castle_rights.white, (false, false));
+
+        // Kings can't be removed or replaced and pawns can't go on back ranks
+
+        assert!(game.clear_piece(Point(3, 3)).is_err());
+        assert!(game.set_piece(Point(7, 4), Piece { side: White, typ: Queen }).is_err());
+        assert!(game.set_piece(Point(7, 0), Piece { side: White, typ: Pawn }).is_err());
+
+        let fen = "rnbqkbnr/pppppppp/8/8/3K4/8/PPPPPPPP/1NBQ1BNR w kq - 0 1";
+        let mut expected = Game::from_fen(fen).unwrap();
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.zobrist_table.key(), expected.zobrist_table.key());
+        assert_eq!(game.perft(3), expected.perft(3));
+
+        assert!(game.automatic_threefold);
+        assert!(!game.fifty_move_rule);
+        assert!(!game.repetition_rule);
+        game.set_turn(Black).unwrap();
+        assert!(game.automatic_threefold);
+        assert_eq!(game.start_turn, Black);
+    }
+}
+
+#[cfg(all(test, feature = "board-9x9"))]
+mod board_size_tests {
+    use super::*;
+    use crate::engine;
+    use crate::game::GameResult;
+    use crate::move_input;
+    use rand_chacha::rand_core::RngCore;
+
+    // Board of another size starts from derived squares, castles both ways, and plays a legal game
+
+    #[test]
+    fn other_board_size() {
+        let mut game = Game::new();
+        assert_eq!(game.to_fen(), "rnbqkqbnr/ppppppppp/9/9/9/9/9/PPPPPPPPP/RNBQKQBNR w KQkq - 0 1");
+        assert_eq!(game.perft(1), 2 * SIZE as u64 + 4);
+
+        // Castle both ways with king moving two columns and rook landing next to it
+
+        let castle = Game::from_fen("r3k3r/ppppppppp/9/9/9/9/9/PPPPPPPPP/R3K3R w KQkq - 0 1").unwrap();
+        for (input, king, rook) in [("e1c1", Point(0, 2), Point(0, 3)), ("e1g1", Point(0, 6), Point(0, 5))] {
+            let mut game = castle.clone();
+            let (mov, promotion) = move_input::parse_coordinates(input).unwrap();
+            game.make_move(mov, promotion).unwrap();
+            assert_eq!(game.board[king.0][king.1], Square::full(White, King), "{input}");
+            assert_eq!(game.board[rook.0][rook.1], Square::full(White, Rook), "{input}");
+            assert_eq!(game.castle_rights.white, (false, false));
+        }
+
+        // Play random legal moves until game ends, checking each position survives a FEN round trip
+
+        let mut rng = engine::create_rng(engine::DEFAULT_SEED);
+        for _ in 0..400 {
+            if game.get_game_result() != GameResult::None {
+                break;
+            }
+            let legal = game.legal_moves();
+            let (mov, promotion) = legal[rng.next_u32() as usize % legal.len()];
+            assert!(game.is_legal_move(mov));
+            game.make_move(mov, promotion).unwrap();
+            let fen = game.to_fen();
+            assert_eq!(Game::from_fen(&fen).unwrap().to_fen(), fen);
         }
+        assert!(game.history.len() > 100);
     }
 }
\ No newline at end of file
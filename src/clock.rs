@@ -0,0 +1,148 @@
+// Imports
+
+use crate::board::Side::{ self, White, Black };
+
+use std::fmt;
+use std::time::Duration;
+
+// Chess clock with remaining time for each side and increment per move
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub struct Clock {
+    pub white: Duration,    // Remaining time for white
+    pub black: Duration,    // Remaining time for black
+    pub increment: Duration // Time added after each completed move
+}
+
+impl Clock {
+    // Create clock with base minutes for both sides and increment seconds
+
+    pub fn new(base_minutes: u64, increment_seconds: u64) -> Clock {
+        Clock {
+            white: Duration::from_secs(base_minutes * 60),
+            black: Duration::from_secs(base_minutes * 60),
+            increment: Duration::from_secs(increment_seconds)
+        }
+    }
+
+    // Parse clock from base minutes and optional increment seconds
+
+    pub fn parse(input: &str) -> Result<Clock, String> {
+        let fields: Vec<&str> = input.split_whitespace().collect();
+        let base_minutes = match fields.first().map(|field| field.parse()) {
+            Some(Ok(minutes)) if minutes > 0 => minutes,
+            _ => return Err(format!("'{input}' isn't a valid time control, please try again"))
+        };
+        let increment_seconds = match fields.get(1).map(|field| field.parse()) {
+            Some(Ok(seconds)) if fields.len() == 2 => seconds,
+            None => 0,
+            _ => return Err(format!("'{input}' isn't a valid time control, please try again"))
+        };
+        Ok(Clock::new(base_minutes, increment_seconds))
+    }
+
+    // Get remaining time for side
+
+    pub fn get_remaining(&self, side: Side) -> Duration {
+        match side {
+            White => self.white,
+            Black => self.black
+        }
+    }
+
+    // Subtract time used by side and add increment if move was completed
+    // Returns false if side ran out of time, in which case no increment is added
+
+    pub fn use_time(&mut self, side: Side, elapsed: Duration, moved: bool) -> bool {
+        let remaining = match side {
+            White => &mut self.white,
+            Black => &mut self.black
+        };
+        if elapsed >= *remaining {
+            *remaining = Duration::ZERO;
+            return false;
+        }
+
+        *remaining -= elapsed;
+        if moved {
+            *remaining += self.increment;
+        }
+        true
+    }
+}
+
+// Display remaining time for both sides
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} | {} {}", White.name(), format_time(self.white), Black.name(), format_time(self.black))
+    }
+}
+
+// Format duration as minutes and seconds
+
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+    use crate::board::Game;
+    use crate::game::{ GameResult, WinType, DrawType };
+    use crate::move_input;
+
+    // Using exactly the remaining time flags the side without increment
+
+    #[test]
+    fn flag_on_exact_time() {
+        let mut clock = Clock::new(1, 0);
+        assert!(!clock.use_time(White, Duration::from_secs(60), true));
+        assert_eq!(clock.white, Duration::ZERO);
+        assert_eq!(clock.black, Duration::from_secs(60));
+    }
+
+    // Increment is only added after completed moves
+
+    #[test]
+    fn increment_after_moves() {
+        let mut clock = Clock::new(1, 2);
+        assert!(clock.use_time(White, Duration::from_secs(5), false));
+        assert_eq!(clock.white, Duration::from_secs(55));
+        assert!(clock.use_time(White, Duration::from_secs(5), true));
+        assert_eq!(clock.white, Duration::from_secs(52));
+        assert!(!clock.use_time(Black, Duration::from_secs(61), true));
+        assert_eq!(clock.black, Duration::ZERO);
+    }
+
+    // Move made after time ran out is taken back and the side loses on time
+
+    #[test]
+    fn flag_takes_back_move() {
+        let mut game = Game::new();
+        game.clock = Some(Clock::new(1, 0));
+        let (mov, promotion) = move_input::parse_coordinates("e2e4").unwrap();
+        game.make_move(mov, promotion).unwrap();
+        assert!(!game.use_clock_time(White, Duration::from_secs(61), true));
+        assert_eq!(game.history.len(), 0);
+        assert_eq!(game.turn, White);
+        assert_eq!(game.get_game_result(), GameResult::Win(Black, WinType::Time));
+    }
+
+    // Running out of time against a bare king is a draw
+
+    #[test]
+    fn flag_against_bare_king() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        game.clock = Some(Clock::new(1, 0));
+        assert!(!game.use_clock_time(White, Duration::from_secs(60), false));
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::TimeMaterial));
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        game.clock = Some(Clock::new(1, 0));
+        assert!(!game.use_clock_time(Black, Duration::from_secs(60), false));
+        assert_eq!(game.get_game_result(), GameResult::Win(White, WinType::Time));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,1441 @@
+// Imports
+
+pub mod book;
+mod table;
+
+use crate::board::{ SIZE, INITIAL_POSITIONS };
+use crate::board::Side::{ self, White, Black };
+use crate::board::{ Square, Point, Piece };
+use crate::board::PieceType::{ self, Pawn, Knight, Bishop, Rook, Queen, King };
+use crate::board::Move;
+use crate::board::Game;
+use crate::moves;
+use crate::game::GameResult;
+use book::Book;
+use table::TranspositionTable;
+
+use std::cmp::Reverse;
+use std::env;
+use std::fmt;
+use std::panic;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use rand_chacha::ChaCha8Rng;
+use rand_chacha::rand_core::{ RngCore, SeedableRng };
+
+// Search parameters
+
+const MATE_SCORE: i32 = 100000;
+const MAX_MATE_PLIES: i32 = 1000;
+pub const DEFAULT_SEED: u64 = 52983;
+pub const ANALYSIS_DEPTH: u32 = 3;
+const SELFPLAY_MAX_PLIES: u32 = 400;
+const NULL_MOVE_REDUCTION: u32 = 2;
+const ASPIRATION_WINDOW: i32 = 50;
+const CONTEMPT: i32 = 25;
+const MOP_UP_MATERIAL: i32 = 500;
+const MATE_DRIVE_WEIGHTS: (i32, i32, i32) = (20, 10, 3); // Lone king edge, king distance, and box area weights
+
+// Evaluation weights for material, king safety, mobility, and pawn structure
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub struct EvalParams {
+    pub piece_values: [(PieceType, i32); 5],     // Material value of each piece type except king
+    pub king_attack_penalty: i32,                // Penalty for each attacked square next to king
+    pub pawn_shelter_bonus: i32,                 // Bonus for each pawn in front of castled king
+    pub mobility_weights: [(PieceType, i32); 4], // Bonus for each pseudolegal move by piece type
+    pub doubled_pawn_penalty: i32,               // Penalty for each extra pawn on a file
+    pub isolated_pawn_penalty: i32,              // Penalty for pawn without pawns on adjacent files
+    pub passed_pawn_bonus: [i32; SIZE]           // Passed pawn bonus indexed by ranks advanced
+}
+
+pub const DEFAULT_EVAL_PARAMS: EvalParams = EvalParams {
+    piece_values: [(Pawn, 100), (Knight, 320), (Bishop, 330), (Rook, 500), (Queen, 900)],
+    king_attack_penalty: 8,
+    pawn_shelter_bonus: 12,
+    mobility_weights: [(Knight, 4), (Bishop, 4), (Rook, 2), (Queen, 1)],
+    doubled_pawn_penalty: 15,
+    isolated_pawn_penalty: 12,
+    #[cfg(not(feature = "board-9x9"))]
+    passed_pawn_bonus: [0, 5, 10, 20, 35, 60, 100, 0],
+    #[cfg(feature = "board-9x9")]
+    passed_pawn_bonus: [0, 5, 10, 15, 25, 40, 65, 100, 0]
+};
+
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        DEFAULT_EVAL_PARAMS
+    }
+}
+
+// Statistics from engine search
+
+#[derive(Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct SearchStats {
+    pub nodes: u64,      // Positions visited in search
+    pub depth: u32,      // Search depth in plies, zero for book moves
+    pub elapsed_ms: u64, // Time spent choosing move
+    pub score: i32       // Score of chosen move for white in centipawns
+}
+
+// Move ordering used in search, each level adding to the previous one
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub enum MoveOrdering {
+    Unordered, // Moves in generation order
+    Captures,  // Transposition table move, then captures by victim and attacker value
+    Full       // Killer moves and history scores for quiet moves
+}
+
+// Search state kept for single top-level search
+
+#[derive(Clone)]
+struct SearchState {
+    nodes: u64,                                  // Positions visited in search
+    params: EvalParams,                          // Evaluation weights used for static evaluation
+    ordering: MoveOrdering,                      // Move ordering used in search
+    null_move: bool,                             // Prune with reduced searches after passing turn
+    table: Arc<TranspositionTable>,              // Best moves found for positions shared by search threads
+    killers: Vec<[Option<Move>; 2]>,             // Quiet moves causing beta cutoffs at each ply
+    history: [[u32; SIZE * SIZE]; SIZE * SIZE]   // Quiet move cutoff scores by from and to square
+}
+
+impl SearchState {
+    // Create empty search state with default evaluation weights
+
+    fn new() -> SearchState {
+        SearchState::with_params(DEFAULT_EVAL_PARAMS)
+    }
+
+    // Create empty search state with evaluation weights
+
+    fn with_params(params: EvalParams) -> SearchState {
+        SearchState {
+            nodes: 0,
+            params,
+            ordering: MoveOrdering::Full,
+            null_move: true,
+            table: Arc::new(TranspositionTable::new()),
+            killers: vec![],
+            history: [[0; SIZE * SIZE]; SIZE * SIZE]
+        }
+    }
+
+    // Get killer moves at ply
+
+    fn get_killers(&self, ply: i32) -> [Option<Move>; 2] {
+        self.killers.get(ply as usize).copied().unwrap_or([None, None])
+    }
+
+    // Record quiet move causing beta cutoff at ply and depth
+
+    fn add_cutoff(&mut self, mov: Move, ply: i32, depth: u32) {
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        if self.killers[ply][0] != Some(mov) {
+            self.killers[ply] = [Some(mov), self.killers[ply][0]];
+        }
+
+        let history = &mut self.history[get_index(mov.from)][get_index(mov.to)];
+        *history = history.saturating_add(depth * depth);
+    }
+
+    // Add nodes and history scores searched from copy of state since start
+
+    fn merge(&mut self, start: &SearchState, searched: &SearchState) {
+        self.nodes += searched.nodes - start.nodes;
+        for (from, row) in self.history.iter_mut().enumerate() {
+            for (to, history) in row.iter_mut().enumerate() {
+                *history = history.saturating_add(searched.history[from][to] - start.history[from][to]);
+            }
+        }
+    }
+}
+
+// Engine difficulty level
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard
+}
+
+impl Difficulty {
+    // Get search depth for difficulty
+
+    pub fn get_depth(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 3,
+            Difficulty::Hard => 4
+        }
+    }
+
+    // Get percent chance of playing a random legal move
+
+    pub fn get_random_chance(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 30,
+            Difficulty::Medium | Difficulty::Hard => 0
+        }
+    }
+}
+
+// Get engine seed from BAD_CHESS_SEED environment variable or default seed
+
+pub fn get_seed() -> u64 {
+    env::var("BAD_CHESS_SEED").ok().and_then(|seed| seed.parse().ok()).unwrap_or(DEFAULT_SEED)
+}
+
+// Create random number generator for all engine randomness from seed
+
+pub fn create_rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+// Choose move for side to move at difficulty level with number of search threads
+
+pub fn choose_move(
+    game: &mut Game,
+    book: &Book,
+    difficulty: Difficulty,
+    threads: usize,
+    rng: &mut ChaCha8Rng
+) -> (Option<(Move, Option<PieceType>)>, SearchStats) {
+    // Play random legal move by chance
+
+    if rng.next_u32() % 100 < difficulty.get_random_chance() {
+        let legal = game.legal_moves();
+        if !legal.is_empty() {
+            return (Some(legal[rng.next_u32() as usize % legal.len()]), SearchStats::default());
+        }
+    }
+
+    if threads > 1 {
+        search_parallel(game, book, difficulty.get_depth(), threads, rng)
+    } else {
+        search(game, book, difficulty.get_depth(), rng)
+    }
+}
+
+// Get best move for side to move from opening book or search
+
+pub fn best_move(
+    game: &mut Game,
+    book: &Book,
+    depth: u32,
+    rng: &mut ChaCha8Rng
+) -> Option<(Move, Option<PieceType>)> {
+    search(game, book, depth, rng).0
+}
+
+// Play engine game between search depths without terminal output
+// Games reaching the ply cap without a result return GameResult::None
+
+pub fn play_selfplay(depth_white: u32, depth_black: u32, seed: u64) -> GameResult {
+    play_selfplay_with(Game::new(), (depth_white, &DEFAULT_EVAL_PARAMS), (depth_black, &DEFAULT_EVAL_PARAMS), seed)
+}
+
+// Play engine game from position between search depths and evaluation weights
+
+pub fn play_selfplay_with(
+    mut game: Game,
+    white: (u32, &EvalParams),
+    black: (u32, &EvalParams),
+    seed: u64
+) -> GameResult {
+    let book = Book::new();
+    let mut rng = create_rng(seed);
+
+    for _ in 0..SELFPLAY_MAX_PLIES {
+        let result = game.get_game_result();
+        if !matches!(result, GameResult::None) {
+            return result;
+        }
+
+        // Make best move for side to move
+
+        let (depth, params) = match game.turn {
+            White => white,
+            Black => black
+        };
+        let (mov, promotion) = match search_with(&mut game, &book, depth, params, &mut rng).0 {
+            Some(mov) => mov,
+            None => break
+        };
+        if game.make_move(mov, promotion).is_err() {
+            break;
+        }
+    }
+
+    game.get_game_result()
+}
+
+// Tournament record for first evaluation weights against second
+
+#[derive(Clone, Default, PartialEq)]
+#[derive(Debug)]
+pub struct TournamentResult {
+    pub wins: u32,                      // Games won by first weights
+    pub losses: u32,                    // Games lost by first weights
+    pub draws: u32,                     // Drawn games including games reaching the ply cap
+    pub games: Vec<(Side, GameResult)>  // Side played by first weights and result of each game
+}
+
+impl TournamentResult {
+    // Get score fraction for first weights with draws as half points
+
+    pub fn score(&self) -> f64 {
+        let games = self.wins + self.losses + self.draws;
+        if games == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / games as f64
+    }
+
+    // Estimate Elo difference of first weights from score, none if every game was won or lost
+
+    pub fn elo_difference(&self) -> Option<f64> {
+        let score = self.score();
+        if score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+        Some(400.0 * (score / (1.0 - score)).log10())
+    }
+}
+
+impl fmt::Display for TournamentResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "+{} -{} ={}", self.wins, self.losses, self.draws)?;
+        match self.elo_difference() {
+            Some(elo) => write!(f, " (Elo {elo:+.0})"),
+            None => write!(f, " (Elo unbounded)")
+        }
+    }
+}
+
+// Play games between evaluation weights at search depth and get record
+// Colors alternate every game and each opening is played once with each color,
+// with game seeds derived from seed so tournaments are reproducible
+
+pub fn run_tournament(
+    params_a: &EvalParams,
+    params_b: &EvalParams,
+    games: u32,
+    depth: u32,
+    openings: &[&str],
+    seed: u64
+) -> Result<TournamentResult, String> {
+    let mut result = TournamentResult::default();
+    for index in 0..games {
+        // Get opening position and colors for game
+
+        let game = match openings.len() {
+            0 => Game::new(),
+            count => Game::from_fen(openings[(index as usize / 2) % count])?
+        };
+        let a_side = if index % 2 == 0 { White } else { Black };
+        let (white, black) = match a_side {
+            White => ((depth, params_a), (depth, params_b)),
+            Black => ((depth, params_b), (depth, params_a))
+        };
+
+        // Play game and record result for first weights
+
+        let outcome = play_selfplay_with(game, white, black, seed.wrapping_add(index as u64));
+        match outcome {
+            GameResult::Win(side, _) if side == a_side => result.wins += 1,
+            GameResult::Win(_, _) => result.losses += 1,
+            _ => result.draws += 1
+        }
+        result.games.push((a_side, outcome));
+    }
+    Ok(result)
+}
+
+// Search for best move from opening book or to depth and get search statistics
+
+pub fn search(
+    game: &mut Game,
+    book: &Book,
+    depth: u32,
+    rng: &mut ChaCha8Rng
+) -> (Option<(Move, Option<PieceType>)>, SearchStats) {
+    search_with(game, book, depth, &DEFAULT_EVAL_PARAMS, rng)
+}
+
+// Search for best move from opening book or to depth with evaluation weights
+
+pub fn search_with(
+    game: &mut Game,
+    book: &Book,
+    depth: u32,
+    params: &EvalParams,
+    rng: &mut ChaCha8Rng
+) -> (Option<(Move, Option<PieceType>)>, SearchStats) {
+    search_threads(game, book, depth, params, 1, rng)
+}
+
+// Search for best move from opening book or with root moves split across threads
+// Gets the same move and score as serial search for any number of threads
+
+pub fn search_parallel(
+    game: &mut Game,
+    book: &Book,
+    depth: u32,
+    threads: usize,
+    rng: &mut ChaCha8Rng
+) -> (Option<(Move, Option<PieceType>)>, SearchStats) {
+    search_threads(game, book, depth, &DEFAULT_EVAL_PARAMS, threads, rng)
+}
+
+// Search for best move from opening book or to depth with root moves split across threads
+
+fn search_threads(
+    game: &mut Game,
+    book: &Book,
+    depth: u32,
+    params: &EvalParams,
+    threads: usize,
+    rng: &mut ChaCha8Rng
+) -> (Option<(Move, Option<PieceType>)>, SearchStats) {
+    let start = Instant::now();
+    let mut stats = SearchStats::default();
+
+    // Check opening book moves
+
+    if let Some(mov) = book.get_move(game, rng) {
+        stats.elapsed_ms = start.elapsed().as_millis() as u64;
+        return (Some((mov, None)), stats);
+    }
+
+    // Search with increasing depth and narrow window around previous score
+
+    let mut best = None;
+    let mut best_score = 0;
+    let mut state = SearchState::with_params(*params);
+    for iteration in 1..=depth.max(1) {
+        let mut window = ASPIRATION_WINDOW;
+        loop {
+            let (alpha, beta) = if iteration == 1 || window > MATE_SCORE {
+                (-MATE_SCORE - 1, MATE_SCORE + 1)
+            } else {
+                (best_score - window, best_score + window)
+            };
+
+            // Search again with wider window if score falls outside window
+
+            let (mov, score) = search_root(game, iteration, alpha, beta, &mut state, threads);
+            if mov.is_some() && score > alpha && score < beta {
+                best = mov;
+                best_score = score;
+                break;
+            }
+            if alpha == -MATE_SCORE - 1 && beta == MATE_SCORE + 1 {
+                break;
+            }
+            window *= 4;
+        }
+    }
+
+    stats.score = match game.turn {
+        White => best_score,
+        Black => -best_score
+    };
+    stats.nodes = state.nodes;
+    stats.depth = depth;
+    stats.elapsed_ms = start.elapsed().as_millis() as u64;
+    (best, stats)
+}
+
+// Search root moves in window and get best move and score for side to move
+// Scores at or outside window bounds are only bounds for the true score
+
+fn search_root(
+    game: &mut Game,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    state: &mut SearchState,
+    threads: usize
+) -> (Option<(Move, Option<PieceType>)>, i32) {
+    let legal = game.legal_moves();
+    if threads > 1 && legal.len() > 1 {
+        return search_root_parallel(game, &legal, depth, (alpha, beta), state, threads);
+    }
+
+    let mut best = None;
+    for (mov, promotion) in legal {
+        if game.make_move(mov, promotion).is_err() {
+            continue;
+        }
+        let score = -alpha_beta(game, depth - 1, -beta, -alpha, 1, true, state);
+        game.undo();
+
+        // Keep first move with best score
+
+        if score > alpha {
+            alpha = score;
+            best = Some((mov, promotion));
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    (best, alpha)
+}
+
+// Search root moves split across threads and get the same best move and score as serial root search
+// Moves after the first are searched from copies of the search state sharing one transposition table
+
+fn search_root_parallel(
+    game: &mut Game,
+    legal: &[(Move, Option<PieceType>)],
+    depth: u32,
+    (mut alpha, beta): (i32, i32),
+    state: &mut SearchState,
+    threads: usize
+) -> (Option<(Move, Option<PieceType>)>, i32) {
+    // Search first move with search state
+
+    let mut best = None;
+    let (mov, promotion) = legal[0];
+    if game.make_move(mov, promotion).is_ok() {
+        let score = -alpha_beta(game, depth - 1, -beta, -alpha, 1, true, state);
+        game.undo();
+        if score > alpha {
+            alpha = score;
+            best = Some((mov, promotion));
+        }
+        if alpha >= beta {
+            return (best, alpha);
+        }
+    }
+
+    // Search every nth remaining move on each thread with separate game
+    // Panics in threads are resumed so no root moves are silently skipped
+
+    let rest = &legal[1..];
+    let threads = threads.min(rest.len());
+    let start = &*state;
+    let window = alpha;
+    let mut results: Vec<(usize, i32, SearchState)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|offset| {
+            let mut game = game.clone();
+            scope.spawn(move || {
+                let mut scores = vec![];
+                for n in (offset..rest.len()).step_by(threads) {
+                    let (mov, promotion) = rest[n];
+                    if game.make_move(mov, promotion).is_err() {
+                        continue;
+                    }
+                    let mut searched = start.clone();
+                    let score = -alpha_beta(&mut game, depth - 1, -beta, -window, 1, true, &mut searched);
+                    game.undo();
+                    scores.push((n, score, searched));
+                }
+                scores
+            })
+        }).collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+            .collect()
+    });
+    results.sort_by_key(|(n, _, _)| *n);
+
+    // Keep first move in move order with best score and merge search states
+
+    let mut merged = start.clone();
+    let mut killers = None;
+    for (n, score, searched) in &results {
+        merged.merge(start, searched);
+        if alpha < beta && *score > alpha {
+            alpha = *score;
+            best = Some(rest[*n]);
+            killers = Some(&searched.killers);
+        }
+    }
+    if let Some(killers) = killers {
+        merged.killers = killers.clone();
+    }
+    *state = merged;
+
+    (best, alpha)
+}
+
+// Count nodes in full window search to depth with move ordering without iterative deepening
+
+pub fn count_search_nodes(game: &mut Game, depth: u32, ordering: MoveOrdering) -> u64 {
+    let mut state = SearchState::new();
+    state.ordering = ordering;
+    search_root(game, depth.max(1), -MATE_SCORE - 1, MATE_SCORE + 1, &mut state, 1);
+    state.nodes
+}
+
+// Rank legal moves by search score for side to move and keep best moves
+
+pub fn rank_moves(game: &mut Game, n: usize) -> Vec<(Move, i32)> {
+    let mut ranked = vec![];
+    let mut state = SearchState::new();
+    for (mov, promotion) in game.legal_moves() {
+        // Skip underpromotions with same destination as queen promotion
+
+        if promotion.is_some_and(|promote| promote != Queen) {
+            continue;
+        }
+        if game.make_move(mov, promotion).is_err() {
+            continue;
+        }
+        let score = -alpha_beta(game, ANALYSIS_DEPTH - 1, -MATE_SCORE - 1, MATE_SCORE + 1, 1, true, &mut state);
+        game.undo();
+
+        ranked.push((mov, score));
+    }
+
+    ranked.sort_by_key(|(_, score)| -score);
+    ranked.truncate(n);
+    ranked
+}
+
+// Format score in centipawns or as mate in moves
+
+pub fn format_score(score: i32) -> String {
+    match get_mate_moves(score) {
+        Some(moves) if moves > 0 => format!("#{moves}"),
+        Some(moves) => format!("#-{}", -moves),
+        None => format!("{score:+}")
+    }
+}
+
+// Get moves to mate for mate scores, negative if getting mated
+
+pub fn get_mate_moves(score: i32) -> Option<i32> {
+    if score.abs() > MATE_SCORE - MAX_MATE_PLIES {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        Some(if score > 0 { moves } else { -moves })
+    } else {
+        None
+    }
+}
+
+// Search position with negamax alpha-beta pruning and get score for side to move
+
+fn alpha_beta(
+    game: &mut Game,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    allow_null: bool,
+    state: &mut SearchState
+) -> i32 {
+    state.nodes += 1;
+
+    // Score repeated positions in game history or search path as draws unless repetition rule is off
+    // Draws count as slightly worse than even for the searching side so it avoids repeating unless behind
+
+    if game.repetition_rule && game.zobrist_table.get().copied().unwrap_or(0) > 1 {
+        return if ply % 2 == 0 { -CONTEMPT } else { CONTEMPT };
+    }
+    if depth == 0 {
+        return quiescence(game, alpha, beta, ply, state);
+    }
+
+    // Check for checkmate or stalemate
+
+    let legal = game.generate_legal_moves();
+    let checked = game.in_check();
+    if legal.is_empty() {
+        if checked {
+            return -MATE_SCORE + ply;
+        }
+        return 0;
+    }
+
+    // Prune if passing turn still fails high
+    // Skipped in check and with only king and pawns to avoid zugzwang
+
+    if allow_null && state.null_move && !checked && depth > NULL_MOVE_REDUCTION && has_pieces(game) {
+        let pawn_double_moved = game.make_null_move();
+        let score = -alpha_beta(game, depth - 1 - NULL_MOVE_REDUCTION, -beta, -beta + 1, ply + 1, false, state);
+        game.undo_null_move(pawn_double_moved);
+
+        if score >= beta {
+            return beta;
+        }
+    }
+
+    // Maximize score for side to move with negated opponent scores
+
+    let mut legal = legal;
+    let mut best = None;
+    order_moves(game, &mut legal, state, ply);
+    for (mov, promotion) in legal {
+        let quiet = get_victim(game, mov).is_none() && promotion.is_none();
+        if game.make_move(mov, promotion).is_err() {
+            continue;
+        }
+        let score = -alpha_beta(game, depth - 1, -beta, -alpha, ply + 1, true, state);
+        game.undo();
+
+        if score > alpha {
+            alpha = score;
+            best = Some((mov, promotion));
+        }
+        if alpha >= beta {
+            if quiet {
+                state.add_cutoff(mov, ply, depth);
+            }
+            break;
+        }
+    }
+
+    // Store move raising alpha to search first in later searches
+
+    if let Some(mov) = best {
+        state.table.store(game.hash(), mov, depth);
+    }
+    alpha
+}
+
+// Search captures and queen promotions until position is quiet and get score for side to move
+// Captures losing material by static exchange evaluation are skipped
+
+fn quiescence(
+    game: &mut Game,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    state: &mut SearchState
+) -> i32 {
+    state.nodes += 1;
+
+    // Check for checkmate or stalemate before using static evaluation
+
+    let legal = game.generate_legal_moves();
+    if legal.is_empty() {
+        if game.in_check() {
+            return -MATE_SCORE + ply;
+        }
+        return 0;
+    }
+
+    // Stop if standing pat already fails high
+
+    let stand_pat = match game.turn {
+        White => evaluate_with(game, &state.params),
+        Black => -evaluate_with(game, &state.params)
+    };
+    if stand_pat >= beta {
+        return beta;
+    }
+    alpha = alpha.max(stand_pat);
+
+    // Search captures not losing material
+
+    let mut captures: Vec<(Move, Option<PieceType>)> = legal
+        .into_iter()
+        .filter(|(mov, promotion)| match promotion {
+            Some(promote) => *promote == Queen,
+            None => get_victim(game, *mov).is_some() && see(game, *mov) >= 0
+        })
+        .collect();
+    order_moves(game, &mut captures, state, ply);
+    for (mov, promotion) in captures {
+        if game.make_move(mov, promotion).is_err() {
+            continue;
+        }
+        let score = -quiescence(game, -beta, -alpha, ply + 1, state);
+        game.undo();
+
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    alpha
+}
+
+// Estimate material gained by side to move from capture sequence on move destination
+// Each side recaptures with its least valuable attacker or stops if behind
+
+pub fn see(game: &Game, mov: Move) -> i32 {
+    let mut board = game.board;
+    if game.is_en_passant(mov) {
+        board[mov.from.0][mov.to.1] = Square::Empty;
+    }
+    let get_value = |square: Square| match square {
+        Square::Full(piece) => piece.typ.value(),
+        Square::Empty => 0
+    };
+
+    // Make captures on target square and record gains
+
+    let mut gains = vec![get_victim(game, mov).map_or(0, |victim| victim.value())];
+    let mut attacker = mov.from;
+    let mut side = game.turn;
+    loop {
+        let value = get_value(board[attacker.0][attacker.1]);
+        board[mov.to.0][mov.to.1] = board[attacker.0][attacker.1];
+        board[attacker.0][attacker.1] = Square::Empty;
+        side = side.get_opposite();
+
+        // Recapture with king only if square isn't defended
+
+        let recapture = moves::get_attackers(side, mov.to, &board)
+            .into_iter()
+            .min_by_key(|point| get_value(board[point.0][point.1]))
+            .filter(|point| {
+                board[point.0][point.1] != Square::full(side, King) ||
+                moves::get_attackers(side.get_opposite(), mov.to, &board).is_empty()
+            });
+        match recapture {
+            Some(point) => {
+                gains.push(value - gains[gains.len() - 1]);
+                attacker = point;
+            },
+            None => break
+        }
+    }
+
+    // Resolve gains from last capture with each side choosing to capture or stop
+
+    while let Some(gain) = gains.pop() {
+        match gains.last_mut() {
+            Some(previous) => *previous = -(-*previous).max(gain),
+            None => return gain
+        }
+    }
+    0
+}
+
+// Order transposition table move first, then captures and promotions by most valuable victim
+// and least valuable attacker, then quiet moves by killer moves and history scores
+
+fn order_moves(game: &Game, moves: &mut [(Move, Option<PieceType>)], state: &SearchState, ply: i32) {
+    if state.ordering == MoveOrdering::Unordered {
+        return;
+    }
+    let hash_move = state.table.get_move(game.hash());
+    let killers = match state.ordering {
+        MoveOrdering::Full => state.get_killers(ply),
+        _ => [None, None]
+    };
+    moves.sort_by_cached_key(|&(mov, promotion)| {
+        let attacker = match game.board[mov.from.0][mov.from.1] {
+            Square::Full(piece) => piece.typ,
+            Square::Empty => Pawn
+        };
+
+        // Sort higher scoring moves first in each category with quiet moves last
+
+        let mut score = match get_victim(game, mov) {
+            Some(victim) => victim.value() * 10 - attacker.value() / 100,
+            None => 0
+        };
+        if let Some(promote) = promotion {
+            score += promote.value();
+        }
+
+        let (category, score) = if hash_move == Some((mov, promotion)) {
+            (4, 0)
+        } else if score != 0 {
+            (3, score)
+        } else if killers[0] == Some(mov) {
+            (2, 1)
+        } else if killers[1] == Some(mov) {
+            (2, 0)
+        } else if state.ordering == MoveOrdering::Full {
+            (1, state.history[get_index(mov.from)][get_index(mov.to)] as i32)
+        } else {
+            (1, 0)
+        };
+        Reverse((category, score))
+    });
+}
+
+// Get piece type captured by move including en passant captures
+
+fn get_victim(game: &Game, mov: Move) -> Option<PieceType> {
+    match game.board[mov.to.0][mov.to.1] {
+        Square::Full(piece) => Some(piece.typ),
+        Square::Empty if game.is_en_passant(mov) => Some(Pawn),
+        Square::Empty => None
+    }
+}
+
+// Check if side to move has pieces other than king and pawns
+
+fn has_pieces(game: &Game) -> bool {
+    game.board.iter().flatten().any(|square| match square {
+        Square::Full(piece) => piece.side == game.turn && piece.typ != Pawn && piece.typ != King,
+        Square::Empty => false
+    })
+}
+
+// Get square index for history table
+
+fn get_index(point: Point) -> usize {
+    point.0 * SIZE + point.1
+}
+
+// Evaluate position for white with default weights
+
+pub fn evaluate(game: &Game) -> i32 {
+    evaluate_with(game, &DEFAULT_EVAL_PARAMS)
+}
+
+// Evaluate position for white with evaluation weights
+
+pub fn evaluate_with(game: &Game, params: &EvalParams) -> i32 {
+    let (white, black) = get_material(game, params);
+    let mut score = white - black;
+
+    // Use mating routine for king and queen or rook against lone king
+
+    if let Some((side, piece)) = get_basic_mate(game) {
+        let lone_king = game.king_positions.get_pos(side.get_opposite());
+        let drive = get_mate_drive(lone_king, game.king_positions.get_pos(side), piece);
+        return score + if side == White { drive } else { -drive };
+    }
+
+    // Drive lone king to edge and bring winning king closer to make progress toward mate
+
+    if black == 0 && white >= MOP_UP_MATERIAL {
+        score += get_mop_up(game.king_positions.black, game.king_positions.white);
+    } else if white == 0 && black >= MOP_UP_MATERIAL {
+        score -= get_mop_up(game.king_positions.white, game.king_positions.black);
+    }
+
+    // Penalize attacked squares around kings and reward pawn shelter
+
+    score += get_king_safety(game, White, params) - get_king_safety(game, Black, params);
+
+    // Reward pseudolegal moves available to pieces weighted by piece type
+
+    score += get_mobility(game, White, params) - get_mobility(game, Black, params);
+
+    // Penalize doubled and isolated pawns and reward passed pawns
+
+    score += get_pawn_structure(game, params);
+    score
+}
+
+// Get material for white and black from piece values excluding kings
+
+fn get_material(game: &Game, params: &EvalParams) -> (i32, i32) {
+    let mut material = (0, 0);
+    for square in game.board.iter().flatten() {
+        if let Square::Full(piece) = square {
+            let value = match params.piece_values.iter().find(|(typ, _)| *typ == piece.typ) {
+                Some((_, value)) => *value,
+                None => continue
+            };
+            match piece.side {
+                White => material.0 += value,
+                Black => material.1 += value
+            }
+        }
+    }
+    material
+}
+
+// Get pawn structure score for white from doubled, isolated, and passed pawns
+
+fn get_pawn_structure(game: &Game, params: &EvalParams) -> i32 {
+    // Count pawns on each file for both sides and collect pawn positions
+
+    let mut files = [[0; SIZE]; 2];
+    let mut pawns = vec![];
+    for (row, squares) in game.board.iter().enumerate() {
+        for (col, square) in squares.iter().enumerate() {
+            if let Square::Full(Piece { side, typ: Pawn }) = *square {
+                files[side as usize][col] += 1;
+                pawns.push((side, Point(row, col)));
+            }
+        }
+    }
+
+    let mut score = 0;
+    for side in [White, Black] {
+        let sign = if side == White { 1 } else { -1 };
+        for count in files[side as usize] {
+            if count > 1 {
+                score -= sign * (count - 1) * params.doubled_pawn_penalty;
+            }
+        }
+    }
+
+    for &(side, pos) in &pawns {
+        let sign = if side == White { 1 } else { -1 };
+        let adjacent = pos.1.saturating_sub(1)..(pos.1 + 2).min(SIZE);
+
+        // Isolated pawns have no friendly pawns on adjacent files
+
+        if adjacent.clone().all(|col| col == pos.1 || files[side as usize][col] == 0) {
+            score -= sign * params.isolated_pawn_penalty;
+        }
+
+        // Passed pawns have no enemy pawns ahead on the same or adjacent files
+
+        let ahead = |row: usize| match side {
+            White => row > pos.0,
+            Black => row < pos.0
+        };
+        let blocked = pawns.iter().any(|&(other, other_pos)| {
+            other != side && adjacent.contains(&other_pos.1) && ahead(other_pos.0)
+        });
+        if !blocked {
+            let advanced = match side {
+                White => pos.0,
+                Black => SIZE - 1 - pos.0
+            };
+            score += sign * params.passed_pawn_bonus[advanced];
+        }
+    }
+    score
+}
+
+// Get mobility for side from pseudolegal move counts of minor and major pieces
+
+fn get_mobility(game: &Game, side: Side, params: &EvalParams) -> i32 {
+    let mut moves = vec![];
+    let mut mobility = 0;
+
+    for row in 0..SIZE {
+        for col in 0..SIZE {
+            let piece = match game.board[row][col] {
+                Square::Full(piece) if piece.side == side => piece,
+                _ => continue
+            };
+            let weight = match params.mobility_weights.iter().find(|(typ, _)| *typ == piece.typ) {
+                Some((_, weight)) => *weight,
+                None => continue
+            };
+
+            moves.clear();
+            match piece.typ {
+                Knight => moves::push_knight_moves(side, row, col, &game.board, &mut moves),
+                Bishop => moves::push_bishop_moves(side, row, col, &game.board, &mut moves),
+                Rook => moves::push_rook_moves(side, row, col, &game.board, &mut moves),
+                _ => moves::push_queen_moves(side, row, col, &game.board, &mut moves)
+            }
+            mobility += moves.len() as i32 * weight;
+        }
+    }
+    mobility
+}
+
+// Get king safety from attacked squares next to king and pawns in front of castled king
+
+fn get_king_safety(game: &Game, side: Side, params: &EvalParams) -> i32 {
+    let king = game.king_positions.get_pos(side);
+    let attacked = game.attacked_squares(side.get_opposite());
+    let (home_row, forward) = match side {
+        White => (0, 1),
+        Black => (SIZE - 1, -1)
+    };
+    let rows = king.0.saturating_sub(1)..(king.0 + 2).min(SIZE);
+    let cols = king.1.saturating_sub(1)..(king.1 + 2).min(SIZE);
+
+    let attacks = attacked[rows].iter().flat_map(|row| &row[cols.clone()]).filter(|square| **square).count();
+    let mut safety = -(attacks as i32) * params.king_attack_penalty;
+
+    // Count own pawns on the two ranks in front of a king castled toward either side
+
+    if king.0 == home_row && king.1.abs_diff(INITIAL_POSITIONS.WHITE_KING.1) >= 2 {
+        for distance in 1..=2 {
+            let row = (home_row as i32 + forward * distance) as usize;
+            for col in cols.clone() {
+                if game.board[row][col] == Square::full(side, Pawn) {
+                    safety += params.pawn_shelter_bonus;
+                }
+            }
+        }
+    }
+    safety
+}
+
+// Get winning side and piece position for king and single queen or rook against lone king
+
+fn get_basic_mate(game: &Game) -> Option<(Side, Point)> {
+    let mut pieces = vec![];
+    for (row, squares) in game.board.iter().enumerate() {
+        for (col, square) in squares.iter().enumerate() {
+            if let Square::Full(piece) = square {
+                if piece.typ != King {
+                    pieces.push((*piece, Point(row, col)));
+                }
+            }
+        }
+    }
+    match pieces[..] {
+        [(piece, pos)] if piece.typ == Queen || piece.typ == Rook => Some((piece.side, pos)),
+        _ => None
+    }
+}
+
+// Get bonus for driving lone king to edge, bringing winning king closer, and shrinking
+// the box of ranks and files the lone king is confined to by the queen or rook
+
+fn get_mate_drive(lone_king: Point, king: Point, piece: Point) -> i32 {
+    let center_distance = |index: usize| (2 * index as i32 - (SIZE as i32 - 1)).abs() / 2;
+    let edge = center_distance(lone_king.0) + center_distance(lone_king.1);
+    let distance = lone_king.0.abs_diff(king.0).max(lone_king.1.abs_diff(king.1));
+
+    let box_size = |lone: usize, piece: usize| match lone.cmp(&piece) {
+        std::cmp::Ordering::Less => piece,
+        std::cmp::Ordering::Greater => SIZE - 1 - piece,
+        std::cmp::Ordering::Equal => SIZE
+    };
+    let area = box_size(lone_king.0, piece.0) * box_size(lone_king.1, piece.1);
+
+    let (edge_weight, distance_weight, area_weight) = MATE_DRIVE_WEIGHTS;
+    edge * edge_weight + (SIZE - distance) as i32 * distance_weight + (SIZE * SIZE - area) as i32 * area_weight
+}
+
+// Get bonus for lone king distance from center and closeness of winning king
+
+fn get_mop_up(lone_king: Point, king: Point) -> i32 {
+    let center_distance = |index: usize| (2 * index as i32 - (SIZE as i32 - 1)).abs() / 2;
+    let edge = center_distance(lone_king.0) + center_distance(lone_king.1);
+    let distance = lone_king.0.abs_diff(king.0) + lone_king.1.abs_diff(king.1);
+    edge * 10 + (2 * SIZE - distance) as i32 * 4
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+    use crate::move_input;
+    use crate::board::CastleRights;
+    use crate::game::WinType;
+
+    fn mov(input: &str) -> Move {
+        move_input::parse_coordinates(input).unwrap().0
+    }
+
+    fn search_full_window(fen: &str, depth: u32, state: &mut SearchState) -> (Option<(Move, Option<PieceType>)>, i32) {
+        let mut game = Game::from_fen(fen).unwrap();
+        search_root(&mut game, depth, -MATE_SCORE - 1, MATE_SCORE + 1, state, 1)
+    }
+
+    fn best(fen: &str, depth: u32) -> ((Move, Option<PieceType>), SearchStats) {
+        let mut game = Game::from_fen(fen).unwrap();
+        let (mov, stats) = search(&mut game, &Book::empty(), depth, &mut create_rng(DEFAULT_SEED));
+        (mov.unwrap(), stats)
+    }
+
+    // Engine playing black picks mirror of move it picks as white in mirrored position
+
+    #[test]
+    fn mirrored_positions() {
+        let positions = [
+            ("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", "4k3/3r4/8/8/3Q4/8/8/4K3 b - - 0 1"),
+            ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1")
+        ];
+        for (white, black) in positions {
+            let ((white_mov, white_promotion), white_stats) = best(white, 3);
+            let ((black_mov, black_promotion), black_stats) = best(black, 3);
+            let mirror = |point: Point| Point(SIZE - 1 - point.0, point.1);
+            assert_eq!(
+                Move { from: mirror(white_mov.from), to: mirror(white_mov.to) },
+                black_mov,
+                "{} mirrors {}",
+                move_input::to_coordinates(white_mov, white_promotion),
+                move_input::to_coordinates(black_mov, black_promotion)
+            );
+            assert_eq!(white_stats.score, -black_stats.score);
+        }
+    }
+
+    // Parallel search gets the same move and score as serial search
+
+    #[test]
+    fn parallel_matches_serial() {
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1"
+        ];
+        for fen in positions {
+            let (serial, serial_stats) = best(fen, 4);
+            for threads in [2, 3, 4] {
+                let mut game = Game::from_fen(fen).unwrap();
+                let (parallel, parallel_stats) = search_parallel(&mut game, &Book::empty(), 4, threads, &mut create_rng(DEFAULT_SEED));
+                assert_eq!(parallel, Some(serial), "{fen} with {threads} threads");
+                assert_eq!(parallel_stats.score, serial_stats.score);
+                assert_eq!(game.to_fen(), fen);
+            }
+        }
+    }
+
+    // Play engine moves for both sides until game ends or ply limit is reached
+
+    fn play_out(game: &mut Game, depth: u32, plies: usize) -> GameResult {
+        let mut rng = create_rng(DEFAULT_SEED);
+        for _ in 0..plies {
+            if game.get_game_result() != GameResult::None {
+                break;
+            }
+            let (mov, promotion) = best_move(game, &Book::empty(), depth, &mut rng).unwrap();
+            game.make_move(mov, promotion).unwrap();
+        }
+        game.get_game_result()
+    }
+
+    // King and queen against king is mated without repeating or stalemating
+
+    #[test]
+    fn queen_mates_lone_king() {
+        let positions = [
+            ("8/8/8/4k3/8/8/8/3QK3 w - - 0 1", White),
+            ("k7/8/8/8/8/8/8/1Q4K1 w - - 0 1", White),
+            ("7k/8/5K2/8/8/8/8/Q7 b - - 0 1", White),
+            ("3qk3/8/8/8/4K3/8/8/8 b - - 0 1", Black)
+        ];
+        for (fen, winner) in positions {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_eq!(play_out(&mut game, 3, 100), GameResult::Win(winner, WinType::Checkmate), "{fen}");
+        }
+    }
+
+    // Static exchange evaluation with defenders and x-ray attackers
+
+    #[test]
+    fn static_exchange() {
+        let exchanges = [
+            ("4k3/8/2p5/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5", 100 - 500),
+            ("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1", "e4d5", 320),
+            ("4k3/8/2p5/3p4/8/4N3/8/3RK3 w - - 0 1", "e3d5", 100 - 320 + 100),
+            ("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", 100),
+            ("4k3/4p3/3p4/8/8/8/8/3QK3 w - - 0 1", "d1d6", 100 - 900)
+        ];
+        for (fen, input, value) in exchanges {
+            let game = Game::from_fen(fen).unwrap();
+            assert_eq!(see(&game, mov(input)), value, "{fen} {input}");
+        }
+        let game = Game::from_fen("4k3/8/3n4/8/4N3/8/8/3RK3 b - - 0 1").unwrap();
+        assert!(see(&game, mov("d6e4")) > 0);
+    }
+
+    // King only recaptures in exchange when square is not defended
+
+    #[test]
+    fn static_exchange_king() {
+        let game = Game::from_fen("8/8/4k3/3p4/8/8/Q7/3RK3 w - - 0 1").unwrap();
+        assert_eq!(see(&game, mov("d1d5")), 100);
+        let game = Game::from_fen("8/8/4k3/3p4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(see(&game, mov("d1d5")), 100 - 500);
+    }
+
+    // King with pawn shelter is evaluated better than king with pushed pawns
+
+    #[test]
+    fn king_safety() {
+        let sheltered = Game::from_fen("r4rk1/ppp2ppp/8/8/8/8/PPP2PPP/R4RK1 w - - 0 1").unwrap();
+        let exposed = Game::from_fen("r4rk1/ppp2ppp/8/8/5PPP/8/PPP5/R4RK1 w - - 0 1").unwrap();
+        assert!(evaluate(&sheltered) > evaluate(&exposed), "{} {}", evaluate(&sheltered), evaluate(&exposed));
+        assert_eq!(evaluate(&Game::new()), 0);
+    }
+
+    // Pieces hemmed in by own pawns are evaluated worse than developed pieces
+
+    #[test]
+    fn mobility() {
+        let cramped = Game::from_fen("4k3/8/8/8/8/1P6/PBP5/RN2K3 w - - 0 1").unwrap();
+        let open = Game::from_fen("4k3/8/8/8/3B4/1P6/P1P5/R3K1N1 w - - 0 1").unwrap();
+        assert!(evaluate(&cramped) < evaluate(&open), "{} {}", evaluate(&cramped), evaluate(&open));
+    }
+
+    // Passed pawns are rewarded and doubled pawns are penalized
+
+    #[test]
+    fn pawn_structure() {
+        let passed = Game::from_fen("4k3/8/8/3P4/8/8/5PPP/4K3 w - - 0 1").unwrap();
+        let blockaded = Game::from_fen("4k3/3p4/8/3P4/8/8/5PPP/4K3 w - - 0 1").unwrap();
+        assert!(evaluate(&passed) > evaluate(&blockaded) + 100, "{} {}", evaluate(&passed), evaluate(&blockaded));
+
+        let healthy = Game::from_fen("4k3/8/8/8/8/8/5PPP/4K3 w - - 0 1").unwrap();
+        let doubled = Game::from_fen("4k3/8/8/8/8/6P1/5PP1/4K3 w - - 0 1").unwrap();
+        assert!(evaluate(&healthy) > evaluate(&doubled), "{} {}", evaluate(&healthy), evaluate(&doubled));
+    }
+
+    // Engine avoids stalemating when winning and takes stalemate when losing
+
+    #[test]
+    fn stalemate_handling() {
+        let mut rng = create_rng(DEFAULT_SEED);
+        let mut game = Game::from_fen("k7/2K5/8/1Q6/8/8/8/8 w - - 0 1").unwrap();
+        for depth in 1..=4 {
+            let (best, _) = search(&mut game, &Book::empty(), depth, &mut rng);
+            assert_ne!(best.unwrap().0, mov("b5b6"), "depth {depth}");
+        }
+
+        let mut game = Game::from_fen("k7/p7/P7/8/8/4K1B1/7R/7q w - - 0 1").unwrap();
+        for depth in 1..=3 {
+            let (best, stats) = search(&mut game, &Book::empty(), depth, &mut rng);
+            if depth == 1 {
+                assert_eq!(best.unwrap().0, mov("h2h1"));
+            }
+            assert_eq!(stats.score, 0, "depth {depth}");
+        }
+    }
+
+    // Engine playing both sides from winning position mates without repeating position three times
+    // whether or not the repetition rule makes repeated positions draws
+
+    #[test]
+    fn avoid_repetition_when_winning() {
+        for repetition_rule in [true, false] {
+            let mut rng = create_rng(DEFAULT_SEED);
+            let mut game = Game::from_fen("6k1/5pp1/8/8/8/8/5PP1/3Q2K1 w - - 0 1").unwrap();
+            game.repetition_rule = repetition_rule;
+            for _ in 0..60 {
+                let (best, _) = search(&mut game, &Book::empty(), 3, &mut rng);
+                let Some((mov, promotion)) = best else {
+                    break;
+                };
+                game.make_move(mov, promotion).unwrap();
+                let count = game.zobrist_table.get().copied().unwrap_or(0);
+                assert!(count < 3, "repeated at ply {} with repetition rule {repetition_rule}", game.history.len());
+            }
+            assert_eq!(game.get_game_result(), GameResult::Win(White, WinType::Checkmate), "repetition rule {repetition_rule}");
+        }
+    }
+
+    // Get random legal position with kings and one white piece
+
+    fn random_position(rng: &mut ChaCha8Rng, typ: PieceType) -> Game {
+        loop {
+            let mut square = || Point(rng.next_u32() as usize % SIZE, rng.next_u32() as usize % SIZE);
+            let (white_king, black_king, piece) = (square(), square(), square());
+            if white_king == black_king || white_king == piece || black_king == piece {
+                continue;
+            }
+            let mut board = [[Square::Empty; SIZE]; SIZE];
+            board[white_king.0][white_king.1] = Square::full(White, King);
+            board[black_king.0][black_king.1] = Square::full(Black, King);
+            board[piece.0][piece.1] = Square::full(White, typ);
+            let castle_rights = CastleRights { white: (false, false), black: (false, false) };
+            if let Ok(mut game) = Game::from_position(White, board, castle_rights, None, 0, 1) {
+                if game.get_game_result() == GameResult::None {
+                    return game;
+                }
+            }
+        }
+    }
+
+    // King and queen or king and rook mates lone king from random positions
+
+    #[test]
+    fn random_mates() {
+        let mut rng = create_rng(DEFAULT_SEED);
+        for typ in [Queen, Rook] {
+            for _ in 0..20 {
+                let mut game = random_position(&mut rng, typ);
+                let fen = game.to_fen();
+                assert_eq!(play_out(&mut game, 3, 100), GameResult::Win(White, WinType::Checkmate), "{fen}");
+            }
+        }
+    }
+
+    // Tournament alternates colors, records each game, and is reproducible for seed
+
+    #[test]
+    fn tournament_record() {
+        let openings = ["4k3/8/8/8/8/8/8/3QK3 w - - 0 1"];
+        let result = run_tournament(&DEFAULT_EVAL_PARAMS, &DEFAULT_EVAL_PARAMS, 2, 1, &openings, DEFAULT_SEED).unwrap();
+        let sides: Vec<Side> = result.games.iter().map(|&(side, _)| side).collect();
+        assert_eq!(sides, [White, Black]);
+        assert_eq!(result.wins, 1);
+        assert_eq!(result.losses, 1);
+        assert_eq!(result.draws, 0);
+        assert_eq!(result.to_string(), "+1 -1 =0 (Elo +0)");
+        assert_eq!(run_tournament(&DEFAULT_EVAL_PARAMS, &DEFAULT_EVAL_PARAMS, 2, 1, &openings, DEFAULT_SEED), Ok(result));
+    }
+
+    // Move ordering searches fewer nodes without changing best move or score
+
+    #[test]
+    fn ordering_keeps_best_move() {
+        let positions = [
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"
+        ];
+        for fen in positions {
+            let mut unordered = SearchState::new();
+            unordered.ordering = MoveOrdering::Unordered;
+            let mut ordered = SearchState::new();
+            assert_eq!(search_full_window(fen, 3, &mut ordered), search_full_window(fen, 3, &mut unordered), "{fen}");
+            assert!(ordered.nodes < unordered.nodes, "{fen}: {} {}", ordered.nodes, unordered.nodes);
+        }
+    }
+
+    // Null move pruning searches fewer nodes without changing best move
+
+    #[test]
+    fn null_move_keeps_best_move() {
+        let fen = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R1BQKB1R w KQ - 0 8";
+        let mut pruned = SearchState::new();
+        let mut unpruned = SearchState::new();
+        unpruned.null_move = false;
+        let (pruned_move, _) = search_full_window(fen, 4, &mut pruned);
+        let (unpruned_move, _) = search_full_window(fen, 4, &mut unpruned);
+        assert_eq!(pruned_move, unpruned_move);
+        assert!(pruned.nodes < unpruned.nodes, "{} {}", pruned.nodes, unpruned.nodes);
+    }
+
+    // Killer and history ordering keeps best move and score
+
+    #[test]
+    fn killers_keep_best_move() {
+        let positions = [
+            "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R1BQKB1R w KQ - 0 8",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"
+        ];
+        for fen in positions {
+            let mut captures = SearchState::new();
+            captures.ordering = MoveOrdering::Captures;
+            let mut full = SearchState::new();
+            assert_eq!(search_full_window(fen, 4, &mut full), search_full_window(fen, 4, &mut captures), "{fen}");
+        }
+    }
+
+    // Score falling outside aspiration window between iterations is searched again to full window score
+
+    #[test]
+    fn aspiration_research() {
+        let positions = [
+            ("r3k3/8/8/1N6/8/8/8/4K3 w - - 0 1", 2),
+            ("6k1/8/5K2/8/8/8/8/1R6 b - - 0 1", 2),
+            ("6k1/8/5K2/8/8/8/8/1R6 b - - 0 1", 4)
+        ];
+        for (fen, depth) in positions {
+            let (_, previous_score) = search_full_window(fen, depth - 1, &mut SearchState::new());
+            let (full_move, full_score) = search_full_window(fen, depth, &mut SearchState::new());
+            assert!((full_score - previous_score).abs() > ASPIRATION_WINDOW, "{fen} at depth {depth}");
+
+            let (mov, stats) = best(fen, depth);
+            let score = match Game::from_fen(fen).unwrap().turn {
+                White => stats.score,
+                Black => -stats.score
+            };
+            assert_eq!(Some(mov), full_move, "{fen} at depth {depth}");
+            assert_eq!(score, full_score, "{fen} at depth {depth}");
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+// Imports
+
+use crate::board::Move;
+use crate::board::Game;
+use crate::move_input;
+
+use std::collections::HashMap;
+use nohash_hasher::BuildNoHashHasher;
+use rand_chacha::ChaCha8Rng;
+use rand_chacha::rand_core::RngCore;
+
+// Opening lines in coordinate notation with weights
+
+const BOOK_LINES: [(&str, u32); 10] = [
+    ("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7", 4), // Ruy Lopez
+    ("e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 c2c3 g8f6", 3),           // Italian game
+    ("e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6", 4), // Sicilian Najdorf
+    ("e2e4 e7e6 d2d4 d7d5 b1c3 g8f6 c1g5 f8e7", 2),           // French defense
+    ("e2e4 c7c6 d2d4 d7d5 b1c3 d5e4 c3e4 c8f5", 2),           // Caro-Kann defense
+    ("d2d4 d7d5 c2c4 e7e6 b1c3 g8f6 c1g5 f8e7", 4),           // Queen's gambit declined
+    ("d2d4 d7d5 c2c4 c7c6 g1f3 g8f6 b1c3 d5c4", 2),           // Slav defense
+    ("d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6 g1f3 e8g8", 3), // King's Indian defense
+    ("c2c4 e7e5 b1c3 g8f6 g1f3 b8c6 g2g3 d7d5", 2),           // English opening
+    ("g1f3 d7d5 g2g3 g8f6 f1g2 c7c6 e1g1 c8g4", 1)            // Reti opening
+];
+
+// Opening book mapping Zobrist keys to weighted moves
+
+pub struct Book {
+    entries: HashMap<u64, Vec<(Move, u32)>, BuildNoHashHasher<u64>>
+}
+
+impl Book {
+    // Create book from built in opening lines
+
+    pub fn new() -> Book {
+        let mut book = Book::empty();
+        for (line, weight) in BOOK_LINES {
+            book.add_line(line, weight);
+        }
+        book
+    }
+
+    // Create book with no moves
+
+    pub fn empty() -> Book {
+        Book {
+            entries: HashMap::with_hasher(BuildNoHashHasher::default())
+        }
+    }
+
+    // Add opening line from start position to book
+
+    pub fn add_line(&mut self, line: &str, weight: u32) {
+        let mut game = Game::new();
+        for text in line.split_whitespace() {
+            // Stop at invalid or illegal moves
+
+            let mov = match move_input::parse_coordinates(text) {
+                Some((mov, None)) => mov,
+                _ => return
+            };
+            if !game.legal_moves().contains(&(mov, None)) {
+                return;
+            }
+
+            // Add weight to move in position
+
+            let moves = self.entries.entry(game.hash()).or_default();
+            match moves.iter_mut().find(|(book_mov, _)| *book_mov == mov) {
+                Some((_, book_weight)) => *book_weight += weight,
+                None => moves.push((mov, weight))
+            }
+
+            if game.make_move(mov, None).is_err() {
+                return;
+            }
+        }
+    }
+
+    // Choose weighted book move for position
+
+    pub fn get_move(&self, game: &Game, rng: &mut ChaCha8Rng) -> Option<Move> {
+        let moves = self.entries.get(&game.hash())?;
+        let total: u32 = moves.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut choice = rng.next_u32() % total;
+        for (mov, weight) in moves {
+            if choice < *weight {
+                return Some(*mov);
+            }
+            choice -= weight;
+        }
+        None
+    }
+}
+
+impl Default for Book {
+    fn default() -> Book {
+        Book::new()
+    }
+}
\ No newline at end of file
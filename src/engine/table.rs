@@ -0,0 +1,110 @@
+// Imports
+
+use crate::board::Move;
+use crate::board::PieceType;
+
+use std::collections::HashMap;
+use std::sync::{ Mutex, MutexGuard, PoisonError };
+use nohash_hasher::BuildNoHashHasher;
+
+// Table parameters
+
+const SHARDS: usize = 16;
+
+// Best move found in search for position and depth it was searched to
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+struct Entry {
+    mov: (Move, Option<PieceType>),
+    depth: u32
+}
+
+type Shard = HashMap<u64, Entry, BuildNoHashHasher<u64>>;
+
+// Transposition table mapping Zobrist keys to best moves found in search
+// Entries are split by key across locked shards so search threads can share one table
+// Moves are only used to order moves, so scores don't depend on table contents
+
+#[derive(Debug)]
+pub struct TranspositionTable {
+    shards: Vec<Mutex<Shard>>
+}
+
+impl TranspositionTable {
+    // Create empty table
+
+    pub fn new() -> TranspositionTable {
+        TranspositionTable {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::with_hasher(BuildNoHashHasher::default()))).collect()
+        }
+    }
+
+    // Get best move stored for position
+
+    pub fn get_move(&self, key: u64) -> Option<(Move, Option<PieceType>)> {
+        self.lock(key).get(&key).map(|entry| entry.mov)
+    }
+
+    // Store best move for position unless a move from a deeper search is stored
+
+    pub fn store(&self, key: u64, mov: (Move, Option<PieceType>), depth: u32) {
+        let mut shard = self.lock(key);
+        let entry = shard.entry(key).or_insert(Entry { mov, depth });
+        if depth >= entry.depth {
+            *entry = Entry { mov, depth };
+        }
+    }
+
+    // Lock shard holding key, using the entries of a thread that panicked while holding the lock
+
+    fn lock(&self, key: u64) -> MutexGuard<'_, Shard> {
+        self.shards[key as usize % SHARDS].lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> TranspositionTable {
+        TranspositionTable::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_input;
+    use std::thread;
+
+    fn mov(input: &str) -> (Move, Option<PieceType>) {
+        move_input::parse_coordinates(input).unwrap()
+    }
+
+    // Moves from shallower searches don't replace moves from deeper searches
+
+    #[test]
+    fn store_keeps_deeper_move() {
+        let table = TranspositionTable::new();
+        assert_eq!(table.get_move(1), None);
+        table.store(1, mov("e2e4"), 3);
+        table.store(1, mov("d2d4"), 2);
+        assert_eq!(table.get_move(1), Some(mov("e2e4")));
+        table.store(1, mov("g1f3"), 3);
+        assert_eq!(table.get_move(1), Some(mov("g1f3")));
+    }
+
+    // Threads storing moves in one table see each other's moves
+
+    #[test]
+    fn shared_between_threads() {
+        let table = TranspositionTable::new();
+        thread::scope(|scope| {
+            for key in 0..4 {
+                let table = &table;
+                scope.spawn(move || table.store(key, mov("e2e4"), 1));
+            }
+        });
+        for key in 0..4 {
+            assert_eq!(table.get_move(key), Some(mov("e2e4")));
+        }
+    }
+}
\ No newline at end of file
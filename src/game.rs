@@ -1,145 +1,420 @@
 // Imports
 
-use crate::board::{ SIZE, INITIAL_POSITIONS, CASTLED_POSITIONS };
+use crate::board::{ SIZE, PROMOTION_PIECES, PROMOTION_ROWS, INITIAL_POSITIONS, CASTLED_POSITIONS };
 use crate::board::Side::{ self, White, Black };
 use crate::board::{ Square, Point };
 use crate::board::Piece;
-use crate::board::PieceType::{ Pawn, Knight, Bishop, Rook, Queen, King };
-use crate::board::Move;
+use crate::board::PieceType::{ self, Pawn, Knight, Bishop, Rook, Queen, King };
+use crate::board::{ Move, MoveRecord };
 use crate::board::CastleDirection;
 use crate::board::Game;
 use crate::moves;
 use crate::move_input;
 use crate::move_input::{ PlayerMove, File, MoveType };
+use crate::util;
 
+use std::fmt;
+use std::fs;
+use std::time::{ Duration, Instant };
 use colored::Colorize;
 
 // Result of game (win, draw, or none)
 
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
 pub enum GameResult {
-    Win(Side),
+    Win(Side, WinType),
     Draw(DrawType),
     None
 }
 
+impl GameResult {
+    // Get PGN result token with asterisk for unfinished games
+
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            GameResult::Win(White, _) => "1-0",
+            GameResult::Win(Black, _) => "0-1",
+            GameResult::Draw(_) => "1/2-1/2",
+            GameResult::None => "*"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub enum WinType {
+    Checkmate,
+    Time
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
 pub enum DrawType {
     Repetition,
     Stalemate,
     Material,
-    FiftyMove
+    FiftyMove,
+    Agreement,
+    TimeMaterial // Side ran out of time but opponent has only a king
+}
+
+// Repetition parameters
+
+const CLAIMABLE_REPETITIONS: u32 = 3;
+const AUTOMATIC_REPETITIONS: u32 = 5;
+
+// PGN parameters
+
+pub const PGN_RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// Display parameters
+
+const TRANSCRIPT_MOVES_PER_LINE: usize = 6;
+const BOARD_WIDTH: usize = 2 * SIZE + 4;
+const TRANSCRIPT_GAP: usize = 4;
+
+// Reason a move input has no legal moves
+
+#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug)]
+pub enum IllegalMove {
+    Unreachable, // No piece of the given type can reach the square
+    Blocked,     // Piece could reach the square on an empty path but is blocked
+    WrongSide,   // Only an opponent piece of the given type can reach the square
+    LeavesCheck  // Move leaves the king in check
+}
+
+impl IllegalMove {
+    // Get error message for illegal move input
+
+    pub fn get_message(&self, input: &str, piece: PieceType) -> String {
+        match self {
+            IllegalMove::Unreachable =>
+                format!("'{input}' isn't a valid move, no {} can reach that square, please try again", piece.get_name()),
+            IllegalMove::Blocked =>
+                format!("'{input}' isn't a valid move, the path is blocked, please try again"),
+            IllegalMove::WrongSide =>
+                format!("'{input}' isn't a valid move, that's not your piece to move, please try again"),
+            IllegalMove::LeavesCheck =>
+                format!("'{input}' leaves the king in check, please try again")
+        }
+    }
+}
+
+// Error from reading, parsing, validating, or making player move
+
+#[derive(Clone, PartialEq)]
+#[derive(Debug)]
+pub enum MoveError {
+    Invalid(String),                         // Input isn't a move in any notation
+    InvalidPiece(String, PieceType),         // Input isn't a valid move for the piece
+    InvalidPawnCapture(String),              // Pawn capture input is malformed
+    InvalidPromotion(String),                // Promotion input is malformed
+    WrongPromotionRank(String, Side),        // Promotion isn't on the last rank for the side to move
+    InvalidPromotionPiece(String),           // Promotion piece input isn't a knight, bishop, rook, or queen
+    InvalidEnPassant(String),                // En passant capture input doesn't match an en passant capture
+    InvalidCapture(String),                  // Capture input doesn't capture an opponent piece
+    MissingPromotion(String),                // Pawn move to the last rank doesn't give a promotion piece
+    InvalidCastle(String),                   // Castle input when castling isn't allowed
+    CastleBlocked(String),                   // Castle passes through pieces or check
+    Illegal(String, PieceType, IllegalMove), // No legal move for the piece matches the input
+    NotLegal(String),                        // Coordinate move isn't legal in the position
+    Ambiguous(String),                       // More than one legal move matches the input
+    NoDrawToClaim,                           // Draw claimed without a claimable draw
+    EndOfInput,                              // Input ended before a move was entered
+    Other(String)                            // Error reading input or making move
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::Invalid(input) =>
+                write!(f, "'{input}' isn't a valid move, please try again"),
+            MoveError::InvalidPiece(input, piece) =>
+                write!(f, "'{input}' isn't a valid {} move, please try again", piece.get_name()),
+            MoveError::InvalidPawnCapture(input) =>
+                write!(f, "'{input}' isn't a valid pawn capture, please try again"),
+            MoveError::InvalidPromotion(input) =>
+                write!(f, "'{input}' isn't a valid pawn promotion, please try again"),
+            MoveError::WrongPromotionRank(input, side) => {
+                let rank = match side {
+                    White => PROMOTION_ROWS.0,
+                    Black => PROMOTION_ROWS.1
+                } + 1;
+                write!(f, "'{input}' promotes on the wrong rank, {} pawns promote on rank {rank}, please try again", side.name().to_lowercase())
+            },
+            MoveError::InvalidPromotionPiece(input) =>
+                write!(f, "'{input}' isn't a valid promotion piece, please try again"),
+            MoveError::InvalidEnPassant(input) =>
+                write!(f, "'{input}' isn't a valid en passant capture, please try again"),
+            MoveError::InvalidCapture(input) =>
+                write!(f, "'{input}' isn't a valid capture, please try again"),
+            MoveError::MissingPromotion(input) =>
+                write!(f, "'{input}' must be a pawn promotion, please try again"),
+            MoveError::InvalidCastle(input) =>
+                write!(f, "'{input}' isn't a valid castle move, please try again"),
+            MoveError::CastleBlocked(input) =>
+                write!(f, "'{input}' castles through pieces or check, please try again"),
+            MoveError::Illegal(input, piece, illegal) =>
+                write!(f, "{}", illegal.get_message(input, *piece)),
+            MoveError::NotLegal(input) =>
+                write!(f, "'{input}' isn't a legal move, please try again"),
+            MoveError::Ambiguous(input) =>
+                write!(f, "'{input}' is an ambiguous move, please try again"),
+            MoveError::NoDrawToClaim =>
+                write!(f, "There is no draw to claim, please try again"),
+            MoveError::EndOfInput =>
+                write!(f, "End of input"),
+            MoveError::Other(message) =>
+                write!(f, "{message}")
+        }
+    }
 }
 
 // Implement play game methods on game
 
 impl Game {
-    // Get player move input and make move
+    // Get player move input and make move with time charged to clock in timed games
 
-    pub fn player_move(&mut self) -> Result<(), String> {
+    pub fn player_move(&mut self) -> Result<(), MoveError> {
+        let start = Instant::now();
+        let side = self.turn;
+        let result = self.read_player_move();
+        let moved = result.is_ok() && self.turn != side;
+        if !self.use_clock_time(side, start.elapsed(), moved) {
+            return Ok(());
+        }
+        result
+    }
+
+    // Charge time used to player clock and add increment after completed moves
+    // A move made after time ran out is taken back and the side is flagged
+    // Returns false if the side ran out of time
+
+    pub fn use_clock_time(&mut self, side: Side, elapsed: Duration, moved: bool) -> bool {
+        let Some(clock) = &mut self.clock else {
+            return true;
+        };
+        if clock.use_time(side, elapsed, moved) {
+            return true;
+        }
+        if moved {
+            self.undo();
+        }
+        self.flagged = Some(side);
+        false
+    }
+
+    // Get prompt with move number, side to play, and check warning
+
+    pub fn get_turn_prompt(&self) -> String {
+        let check = if self.in_check() { " (in check)" } else { "" };
+        format!("Move {} — {} to play{check}:", self.fullmove, self.turn.name())
+    }
+
+    // Read and make player move without clock handling
+
+    fn read_player_move(&mut self) -> Result<(), MoveError> {
         // Display board and get player move data
 
         self.display();
         println!();
-        match self.turn {
-            White => println!("White to play:\n"),
-            Black => println!("Black to play:\n")
+        if self.claimable_draw().is_some() {
+            println!("{}\n", "You can claim a draw by entering 'claim'".bold());
         }
-        let (input, move_data) = move_input::get_player_move()?;
-        move_input::validate_move(&input, move_data, self)?;
+        println!("Enter 'moves' to list legal moves or 'quit' to return to the menu\n");
+        let prompt = self.get_turn_prompt();
+        println!("{prompt}\n");
+        let mut input = move_input::get_player_input()?;
 
-        // Handle castle moves
+        // List legal moves and prompt again without making move
 
-        if let MoveType::Castle(dir) = move_data.typ {
-            let king_pos = self.king_positions.get_pos(self.turn);
-            let (rook_pos, king_mov, rook_mov) = match self.turn {
-                White => {
-                    match dir {
-                        CastleDirection::A => (
-                            INITIAL_POSITIONS.WHITE_ROOKS.0,
-                            CASTLED_POSITIONS.WHITE_KING.0,
-                            CASTLED_POSITIONS.WHITE_ROOKS.0
-                        ),
-                        CastleDirection::H => (
-                            INITIAL_POSITIONS.WHITE_ROOKS.1,
-                            CASTLED_POSITIONS.WHITE_KING.1,
-                            CASTLED_POSITIONS.WHITE_ROOKS.1
-                        )
-                    }
+        while input == "moves" {
+            let mut legal: Vec<String> = self.legal_moves()
+                .into_iter()
+                .map(|(mov, promotion)| move_input::to_san(self, mov, promotion))
+                .collect();
+            legal.sort();
+            println!("\nLegal moves: {}\n", legal.join(", "));
+            println!("{prompt}\n");
+            input = move_input::get_player_input()?;
+        }
+
+        // Quit game and return to menu
+
+        if util::is_quit(&input) {
+            self.quit = true;
+            return Ok(());
+        }
+
+        // Handle draw claims
+
+        if input == "claim" {
+            return match self.claimable_draw() {
+                Some(draw) => {
+                    self.claimed_draw = Some(draw);
+                    Ok(())
                 },
-                Black => {
-                    match dir {
-                        CastleDirection::A => (
-                            INITIAL_POSITIONS.BLACK_ROOKS.0,
-                            CASTLED_POSITIONS.BLACK_KING.0,
-                            CASTLED_POSITIONS.BLACK_ROOKS.0
-                        ),
-                        CastleDirection::H => (
-                            INITIAL_POSITIONS.BLACK_ROOKS.1,
-                            CASTLED_POSITIONS.BLACK_KING.1,
-                            CASTLED_POSITIONS.BLACK_ROOKS.1
-                        )
-                    }
-                }
+                None => Err(MoveError::NoDrawToClaim)
             };
+        }
 
-            // Move pieces
+        // Handle coordinate moves before SAN and prompt for missing promotion piece
 
-            self.board[king_mov.0][king_mov.1] = self.board[king_pos.0][king_pos.1];
-            self.board[rook_mov.0][rook_mov.1] = self.board[rook_pos.0][rook_pos.1];
-            self.board[king_pos.0][king_pos.1] = Square::Empty;
-            self.board[rook_pos.0][rook_pos.1] = Square::Empty;
+        if let Some((mov, promotion)) = move_input::parse_coordinates(&input) {
+            self.validate_coordinate_move(&input, mov)?;
+            let promotion = match promotion {
+                None if self.is_promotion(mov) => Some(move_input::get_promotion()?),
+                promotion => promotion
+            };
+            return self.make_move(mov, promotion).map_err(MoveError::Other);
+        }
+
+        let move_data = move_input::parse_move(&input)?;
+        self.apply_player_move(&input, move_data)
+    }
 
-            // Update king position and castle rights
+    // Check coordinate move is legal with a specific error for moving an opponent piece
 
-            match self.turn {
-                White => {
-                    self.king_positions.white = king_mov;
-                    self.castle_rights.white = (false, false);
-                },
-                Black => {
-                    self.king_positions.black = king_mov;
-                    self.castle_rights.black = (false, false);
-                }
+    pub fn validate_coordinate_move(&mut self, input: &str, mov: Move) -> Result<(), MoveError> {
+        if let Square::Full(piece) = self.board[mov.from.0][mov.from.1] {
+            if piece.side != self.turn {
+                return Err(MoveError::Illegal(String::from(input), piece.typ, IllegalMove::WrongSide));
             }
+        }
+        if !self.is_legal_move(mov) {
+            return Err(MoveError::NotLegal(String::from(input)));
+        }
+        Ok(())
+    }
 
-            // Switch active turn
+    // Validate parsed player move input and make move
 
-            self.turn = self.turn.get_opposite();
-            return Ok(());
+    pub fn apply_player_move(&mut self, input: &str, move_data: PlayerMove) -> Result<(), MoveError> {
+        let move_data = move_input::detect_en_passant(move_data, self);
+        move_input::validate_move(input, move_data, self)?;
+
+        // Handle castle moves
+
+        if let MoveType::Castle(dir) = move_data.typ {
+            let (_, king_mov, _) = get_castle_squares(self.turn, dir);
+            return self.make_move(
+                Move {
+                    from: self.king_positions.get_pos(self.turn),
+                    to: king_mov
+                },
+                None
+            ).map_err(MoveError::Other);
         }
         
         // Check if king is in check after possible moves
 
-        let possible = get_possible_moves(move_data, self);
-        if possible.is_empty() {
-            return Err(format!("'{input}' isn't a valid move, please try again"));
-        }
+        let possible = get_possible_moves(move_data, self)
+            .map_err(|error| MoveError::Illegal(String::from(input), move_data.piece, error))?;
 
         let legal: Vec<Move> = moves::filter_legal_moves(
             self.turn,
             possible,
             &mut self.board,
+            self.pawn_double_moved,
             self.king_positions.get_pos(self.turn)
         ).collect();
         if legal.is_empty() {
-            return Err(format!("'{input}' leaves the king in check, please try again"));
+            return Err(MoveError::Illegal(String::from(input), move_data.piece, IllegalMove::LeavesCheck));
         } else if legal.len() > 1 {
-            return Err(format!("'{input}' is an ambiguous move, please try again"));
+            return Err(MoveError::Ambiguous(String::from(input)));
         }
 
         // Make single legal move
 
-        let mov = legal[0];
-        match move_data.typ {
-            MoveType::Promotion(piece) | MoveType::CapturePromotion(piece) =>
-                self.board[mov.to.0][mov.to.1] = Square::full(self.turn, piece),
-            _ =>
-                self.board[mov.to.0][mov.to.1] = self.board[mov.from.0][mov.from.1]
+        let promotion = match move_data.typ {
+            MoveType::Promotion(piece) | MoveType::CapturePromotion(piece) => Some(piece),
+            _ => None
+        };
+        self.make_move(legal[0], promotion).map_err(MoveError::Other)
+    }
+
+    // Apply validated move and update game state
+
+    pub fn make_move(&mut self, mov: Move, promotion: Option<PieceType>) -> Result<(), String> {
+        // Check moved piece and promotion
+
+        let piece = match self.board[mov.from.0][mov.from.1] {
+            Square::Full(piece) if piece.side == self.turn => piece,
+            _ => return Err(String::from("Invalid move, no piece to move on the starting square"))
+        };
+
+        let last_row = match self.turn {
+            White => PROMOTION_ROWS.0,
+            Black => PROMOTION_ROWS.1
+        };
+        if piece.typ == Pawn && mov.to.0 == last_row {
+            match promotion {
+                Some(promote) if PROMOTION_PIECES.contains(&promote) => (),
+                _ => return Err(String::from("Invalid move, pawn must promote to a knight, bishop, rook, or queen"))
+            }
+        } else if promotion.is_some() {
+            return Err(String::from("Invalid move, only a pawn reaching the last rank can promote"));
         }
-        self.board[mov.from.0][mov.from.1] = Square::Empty;        
+
+        // Find piece captured on destination or en passant square
+
+        let en_passant = self.is_en_passant(mov);
+        let captured = match self.board[mov.to.0][mov.to.1] {
+            Square::Full(piece) => Some(piece),
+            Square::Empty if en_passant => Some(Piece { side: self.turn.get_opposite(), typ: Pawn }),
+            Square::Empty => None
+        };
+        debug_assert!(
+            !matches!(captured, Some(Piece { typ: King, .. })),
+            "move {mov:?} captures a king"
+        );
+
+        // Save previous state for undo and clear legal move cache
+
+        self.legal_cache = None;
+        self.history.push(MoveRecord {
+            mov,
+            promotion,
+            board: self.board,
+            king_positions: self.king_positions,
+            castle_rights: self.castle_rights,
+            pawn_double_moved: self.pawn_double_moved,
+            last_active_ply: self.last_active_ply,
+            captured
+        });
+
+        // Handle castle moves
+
+        if piece.typ == King && mov.from.1.abs_diff(mov.to.1) == 2 {
+            let dir = if mov.to.1 < mov.from.1 { CastleDirection::A } else { CastleDirection::H };
+            let (rook_pos, _, rook_mov) = get_castle_squares(self.turn, dir);
+            self.board[rook_mov.0][rook_mov.1] = self.board[rook_pos.0][rook_pos.1];
+            self.board[rook_pos.0][rook_pos.1] = Square::Empty;
+            debug_assert!(
+                self.board[rook_mov.0][rook_mov.1] == Square::full(self.turn, Rook),
+                "castle {mov:?} didn't move a rook from {rook_pos:?}"
+            );
+        }
+
+        // Handle en passant captures
+
+        if en_passant {
+            self.board[mov.from.0][mov.to.1] = Square::Empty;
+        }
+
+        // Move piece
+
+        self.board[mov.to.0][mov.to.1] = match promotion {
+            Some(promote) => Square::full(self.turn, promote),
+            None => self.board[mov.from.0][mov.from.1]
+        };
+        self.board[mov.from.0][mov.from.1] = Square::Empty;
 
         // Update king position and castle rights
 
-        if move_data.piece == King {
+        if piece.typ == King {
             match self.turn {
                 White => {
                     self.king_positions.white = mov.to;
@@ -150,7 +425,7 @@ impl Game {
                     self.castle_rights.black = (false, false);
                 }
             }
-        } else if move_data.piece == Rook {
+        } else if piece.typ == Rook {
             match self.turn {
                 White => {
                     if mov.from == INITIAL_POSITIONS.WHITE_ROOKS.0 {
@@ -169,111 +444,660 @@ impl Game {
             }
         }
 
-        // Update last active ply counter
+        // Remove castle right for rook captured on its initial square
 
-        match move_data.typ {
-            MoveType::Move => {
-                if move_data.piece == Pawn {
-                    self.last_active_ply = 0;
-                } else {
-                    self.last_active_ply += 1;
-                }
-            },
-            MoveType::Capture | MoveType::Promotion(_) | MoveType::CapturePromotion(_) => self.last_active_ply = 0,
-            MoveType::Castle(_) => self.last_active_ply += 1
+        if mov.to == INITIAL_POSITIONS.WHITE_ROOKS.0 {
+            self.castle_rights.white.0 = false;
+        } else if mov.to == INITIAL_POSITIONS.WHITE_ROOKS.1 {
+            self.castle_rights.white.1 = false;
+        } else if mov.to == INITIAL_POSITIONS.BLACK_ROOKS.0 {
+            self.castle_rights.black.0 = false;
+        } else if mov.to == INITIAL_POSITIONS.BLACK_ROOKS.1 {
+            self.castle_rights.black.1 = false;
         }
 
-        // Switch active turn
+        // Update en passant square and last active ply counter
 
+        self.pawn_double_moved = if piece.typ == Pawn && mov.from.0.abs_diff(mov.to.0) == 2 {
+            Some(mov.to)
+        } else {
+            None
+        };
+
+        if piece.typ == Pawn || captured.is_some() {
+            self.last_active_ply = 0;
+        } else {
+            self.last_active_ply += 1;
+        }
+
+        // Switch active turn and count position occurrence
+
+        if self.turn == Black {
+            self.fullmove += 1;
+        }
         self.turn = self.turn.get_opposite();
+        self.zobrist_table.update_key(self.turn, &self.board, self.castle_rights, self.pawn_double_moved);
+        let count = self.zobrist_table.get().copied().unwrap_or(0);
+        self.zobrist_table.insert(count + 1);
+
+        // Check king positions are in sync with board
+
+        let (white_king, black_king) = (self.king_positions.white, self.king_positions.black);
+        debug_assert!(
+            self.board[white_king.0][white_king.1] == Square::full(White, King),
+            "white king position {white_king:?} is out of sync with board"
+        );
+        debug_assert!(
+            self.board[black_king.0][black_king.1] == Square::full(Black, King),
+            "black king position {black_king:?} is out of sync with board"
+        );
+
         Ok(())
     }
 
-    // Get game result (win, draw, or none)
+    // Undo last move and restore previous game state
 
-    pub fn get_game_result(&mut self) -> GameResult {
-        // Check for 50 move rule (100 plies)
+    pub fn undo(&mut self) -> Option<Move> {
+        let record = self.history.pop()?;
+        self.legal_cache = None;
 
-        if self.last_active_ply == 100 {
-            return GameResult::Draw(DrawType::FiftyMove);
+        // Remove position occurrence
+
+        if let Some(&count) = self.zobrist_table.get() {
+            self.zobrist_table.insert(count - 1);
         }
 
-        // Count legal moves for each side excluding castling
+        // Restore state and switch active turn
 
-        let mut white_moves = 0;
-        let mut black_moves = 0;
+        self.board = record.board;
+        self.king_positions = record.king_positions;
+        self.castle_rights = record.castle_rights;
+        self.pawn_double_moved = record.pawn_double_moved;
+        self.last_active_ply = record.last_active_ply;
+        self.turn = self.turn.get_opposite();
+        if self.turn == Black {
+            self.fullmove -= 1;
+        }
+        self.zobrist_table.update_key(self.turn, &self.board, self.castle_rights, self.pawn_double_moved);
 
+        Some(record.mov)
+    }
+
+    // Pass turn to opponent without moving for search and get previous en passant state
+
+    pub fn make_null_move(&mut self) -> Option<Point> {
+        let pawn_double_moved = self.pawn_double_moved;
+        self.legal_cache = None;
+        self.pawn_double_moved = None;
+        self.turn = self.turn.get_opposite();
+        self.zobrist_table.update_key(self.turn, &self.board, self.castle_rights, self.pawn_double_moved);
+        pawn_double_moved
+    }
+
+    // Undo null move and restore previous en passant state
+
+    pub fn undo_null_move(&mut self, pawn_double_moved: Option<Point>) {
+        self.legal_cache = None;
+        self.pawn_double_moved = pawn_double_moved;
+        self.turn = self.turn.get_opposite();
+        self.zobrist_table.update_key(self.turn, &self.board, self.castle_rights, self.pawn_double_moved);
+    }
+
+    // Save played moves in coordinate notation one per line
+
+    pub fn save_moves(&self, path: &str) -> Result<(), String> {
+        let lines: Vec<String> = self.history.iter()
+            .map(|record| move_input::to_coordinates(record.mov, record.promotion))
+            .collect();
+        fs::write(path, lines.join("\n")).map_err(|error| format!("Error saving moves to '{path}': {error}"))
+    }
+
+    // Load game by replaying saved coordinate moves from start position
+
+    pub fn load_moves(path: &str) -> Result<Game, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("Error loading moves from '{path}': {error}"))?;
+
+        let mut game = Game::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            // Stop at invalid or illegal moves
+
+            let (mov, promotion) = move_input::parse_coordinates(line)
+                .ok_or_else(|| format!("Line {}: '{line}' isn't a valid coordinate move", i + 1))?;
+            if !game.legal_moves().contains(&(mov, promotion)) {
+                return Err(format!("Line {}: '{line}' isn't a legal move in the position", i + 1));
+            }
+            game.make_move(mov, promotion)?;
+        }
+
+        Ok(game)
+    }
+
+    // Load game by replaying SAN moves from PGN text, starting from FEN tag if present
+    // Comments, variations, move numbers, annotations, and result are skipped
+
+    pub fn from_pgn(pgn: &str) -> Result<Game, String> {
+        let mut game = Game::new();
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let line = line.trim();
+            if let Some(tag) = line.strip_prefix('[') {
+                if let Some(fen) = tag.strip_prefix("FEN \"").and_then(|tag| tag.strip_suffix("\"]")) {
+                    game = Game::from_fen(fen)?;
+                }
+                continue;
+            }
+            movetext.push_str(line.split(';').next().unwrap_or(""));
+            movetext.push(' ');
+        }
+
+        // Remove brace comments and parenthesized variations
+
+        let mut main_line = String::new();
+        let mut in_comment = false;
+        let mut variation_depth = 0;
+        for char in movetext.chars() {
+            match char {
+                '{' => in_comment = true,
+                '}' => in_comment = false,
+                '(' if !in_comment => variation_depth += 1,
+                ')' if !in_comment => variation_depth = (variation_depth - 1).max(0),
+                _ if !in_comment && variation_depth == 0 => main_line.push(char),
+                _ => ()
+            }
+        }
+
+        // Replay moves with move number in errors
+
+        for token in main_line.split_whitespace() {
+            if PGN_RESULTS.contains(&token) || token.starts_with('$') || token == "e.p." {
+                continue;
+            }
+            let san = match token.rfind('.') {
+                Some(i) if token[..i].chars().all(|char| char.is_ascii_digit() || char == '.') => &token[i + 1..],
+                _ => token
+            };
+            let san = san.trim_end_matches(['!', '?']);
+            if san.is_empty() {
+                continue;
+            }
+
+            let dots = if game.turn == White { "." } else { "..." };
+            let number = game.fullmove;
+            move_input::parse_move(san)
+                .and_then(|move_data| game.apply_player_move(san, move_data))
+                .map_err(|error| format!("Move {number}{dots} {san}: {error}"))?;
+        }
+        Ok(game)
+    }
+
+    // Count leaf nodes of legal move tree to depth
+
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let legal = self.generate_legal_moves();
+        if depth == 1 {
+            return legal.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for (mov, promotion) in legal {
+            if self.make_move(mov, promotion).is_ok() {
+                nodes += self.perft(depth - 1);
+                self.undo();
+            }
+        }
+        nodes
+    }
+
+    // Count leaf nodes to depth after each legal root move sorted by coordinate notation
+
+    pub fn perft_divide(&mut self, depth: u32) -> Vec<(Move, Option<PieceType>, u64)> {
+        let mut divide = vec![];
+        if depth == 0 {
+            return divide;
+        }
+
+        for (mov, promotion) in self.generate_legal_moves() {
+            if self.make_move(mov, promotion).is_ok() {
+                divide.push((mov, promotion, self.perft(depth - 1)));
+                self.undo();
+            }
+        }
+        divide.sort_by_cached_key(|(mov, promotion, _)| move_input::to_coordinates(*mov, *promotion));
+        divide
+    }
+
+    // Check if move is legal for side to move without changing game
+
+    pub fn is_legal_move(&mut self, mov: Move) -> bool {
+        // Check piece of side to move on starting square
+
+        let piece = match self.board[mov.from.0][mov.from.1] {
+            Square::Full(piece) if piece.side == self.turn => piece,
+            _ => return false
+        };
+
+        // Check castle moves or pseudolegal destination
+
+        if piece.typ == King && mov.from.0 == mov.to.0 && mov.from.1.abs_diff(mov.to.1) == 2 {
+            let dir = if mov.to.1 < mov.from.1 { CastleDirection::A } else { CastleDirection::H };
+            let (_, king_mov, _) = get_castle_squares(self.turn, dir);
+            return mov.to == king_mov &&
+                   self.castle_rights.has_right(self.turn, dir) &&
+                   moves::can_castle(self.turn, dir, &self.board, mov.from);
+        }
+        if !get_moves(piece, mov.from.0, mov.from.1, self).contains(&mov.to) {
+            return false;
+        }
+
+        // Make move and check if king is left in check
+
+        let promotion = if self.is_promotion(mov) { Some(Queen) } else { None };
+        if self.make_move(mov, promotion).is_err() {
+            return false;
+        }
+        let side = self.turn.get_opposite();
+        let legal = !moves::in_check(side, self.king_positions.get_pos(side), &self.board);
+        self.undo();
+
+        legal
+    }
+
+    // Get squares attacked by side
+
+    pub fn attacked_squares(&self, side: Side) -> [[bool; SIZE]; SIZE] {
+        moves::get_attacked_squares(side, &self.board)
+    }
+
+    // Check if side to move is in check
+
+    pub fn in_check(&self) -> bool {
+        moves::in_check(self.turn, self.king_positions.get_pos(self.turn), &self.board)
+    }
+
+    // Check if move is a pawn of side to move capturing en passant
+
+    pub fn is_en_passant(&self, mov: Move) -> bool {
+        moves::is_en_passant(self.turn, mov, &self.board, self.pawn_double_moved)
+    }
+
+    // Get en passant target square if side to move has a legal en passant capture
+
+    pub fn en_passant_target(&self) -> Option<Point> {
+        let pawn = self.pawn_double_moved?;
+        let target = self.en_passant_square()?;
+        let capturer = Square::Full(Piece { side: self.turn, typ: Pawn });
+        let cols = [pawn.1.checked_sub(1), Some(pawn.1 + 1).filter(|&col| col < SIZE)];
+        cols.into_iter().flatten().find(|&col| {
+            if self.board[pawn.0][col] != capturer || self.board[target.0][target.1] != Square::Empty {
+                return false;
+            }
+
+            // Make capture on board copy and check if king is left in check
+
+            let mut board = self.board;
+            board[pawn.0][col] = Square::Empty;
+            board[pawn.0][pawn.1] = Square::Empty;
+            board[target.0][target.1] = capturer;
+            !moves::in_check(self.turn, self.king_positions.get_pos(self.turn), &board)
+        }).map(|_| target)
+    }
+
+    // Check if move is a pawn of side to move reaching the last rank
+
+    pub fn is_promotion(&self, mov: Move) -> bool {
+        let last_row = match self.turn {
+            White => PROMOTION_ROWS.0,
+            Black => PROMOTION_ROWS.1
+        };
+        match self.board[mov.from.0][mov.from.1] {
+            Square::Full(piece) => piece.side == self.turn && piece.typ == Pawn && mov.to.0 == last_row,
+            Square::Empty => false
+        }
+    }
+
+    // Get legal destination squares for piece of side to move on square
+
+    pub fn legal_destinations(&mut self, from: Point) -> Vec<Point> {
+        let mut destinations = vec![];
+        for (mov, _) in self.legal_moves() {
+            if mov.from == from && !destinations.contains(&mov.to) {
+                destinations.push(mov.to);
+            }
+        }
+        destinations
+    }
+
+    // Check if side to move is checkmated
+
+    pub fn is_checkmate(&mut self) -> bool {
+        self.legal_moves().is_empty() &&
+            self.in_check()
+    }
+
+    // Check if side to move is stalemated
+
+    pub fn is_stalemate(&mut self) -> bool {
+        self.legal_moves().is_empty() &&
+            !self.in_check()
+    }
+
+    // Get Zobrist hash of current position updated after each move and undo
+
+    pub fn hash(&self) -> u64 {
+        self.zobrist_table.key()
+    }
+
+    // Get all legal moves for side to move with promotion pieces from cache
+
+    pub fn legal_moves(&mut self) -> Vec<(Move, Option<PieceType>)> {
+        let key = self.hash();
+        if let Some((cached_key, legal)) = &self.legal_cache {
+            if *cached_key == key {
+                return legal.clone();
+            }
+        }
+
+        let legal = self.generate_legal_moves();
+        self.legal_cache = Some((key, legal.clone()));
+        legal
+    }
+
+    // Generate all legal moves for side to move with promotion pieces without cache
+
+    pub fn generate_legal_moves(&mut self) -> Vec<(Move, Option<PieceType>)> {
+        let mut legal = vec![];
+        let last_row = match self.turn {
+            White => PROMOTION_ROWS.0,
+            Black => PROMOTION_ROWS.1
+        };
+
+        let king_pos = self.king_positions.get_pos(self.turn);
+        let checked = moves::in_check(self.turn, king_pos, &self.board);
+        let pinned = moves::get_pinned(self.turn, king_pos, &self.board);
+        let mut points = Vec::with_capacity(32);
+        #[allow(clippy::needless_range_loop)]
         for r in 0..SIZE {
             for c in 0..SIZE {
                 if let Square::Full(piece) = self.board[r][c] {
-                    // Check for legal moves
-
-                    let possible = get_moves(piece, r, c, self)
-                                                  .into_iter()
-                                                  .map(|point| Move { from: Point(r, c), to: point })
-                                                  .collect();
-                    let count = moves::filter_legal_moves(
-                        self.turn,
-                        possible,
-                        &mut self.board,
-                        self.king_positions.get_pos(self.turn)
-                    ).count();
-
-                    match piece.side {
-                        White => white_moves += count,
-                        Black => black_moves += count
+                    if piece.side != self.turn {
+                        continue;
+                    }
+
+                    // Filter legal moves for piece
+
+                    points.clear();
+                    push_board_moves(piece, r, c, &self.board, self.pawn_double_moved, &mut points);
+                    for &point in &points {
+                        // Only check unpinned piece moves when king is in check
+
+                        let mov = Move { from: Point(r, c), to: point };
+                        let fast_path = !checked && !pinned[r][c] && piece.typ != King && !self.is_en_passant(mov);
+                        if !fast_path && !moves::keeps_king_safe(self.turn, mov, &mut self.board, self.pawn_double_moved, king_pos) {
+                            continue;
+                        }
+
+                        if piece.typ == Pawn && mov.to.0 == last_row {
+                            for promote in PROMOTION_PIECES {
+                                legal.push((mov, Some(promote)));
+                            }
+                        } else {
+                            legal.push((mov, None));
+                        }
                     }
                 }
             }
         }
 
-        // Checkmate or stalemate with no moves left
+        // Add castle moves
 
-        if white_moves == 0 {
-            if moves::in_check(White, self.king_positions.get_pos(White), &self.board) {
-                return GameResult::Win(Black);
+        for dir in [CastleDirection::A, CastleDirection::H] {
+            if self.castle_rights.has_right(self.turn, dir) &&
+               moves::can_castle(self.turn, dir, &self.board, king_pos) {
+                let (_, king_mov, _) = get_castle_squares(self.turn, dir);
+                legal.push((Move { from: king_pos, to: king_mov }, None));
             }
-            return GameResult::Draw(DrawType::Stalemate);
-        } else if black_moves == 0 {
-            if moves::in_check(Black, self.king_positions.get_pos(Black), &self.board) {
-                return GameResult::Win(White);
+        }
+
+        legal
+    }
+
+    // Get game result (win, draw, or none)
+    // Precedence after a flag fall: checkmate or stalemate, insufficient material, automatic
+    // repetition, fifty move rule, then a claimed draw, so a mating move that also repeats
+    // a position or ends fifty moves without progress is still a win
+
+    pub fn get_game_result(&mut self) -> GameResult {
+        // Check for side out of time
+
+        if let Some(side) = self.flagged {
+            let (white, black) = self.material();
+            let opponent_material = match side {
+                White => black,
+                Black => white
+            };
+            if opponent_material == 0 {
+                return GameResult::Draw(DrawType::TimeMaterial);
+            }
+            return GameResult::Win(side.get_opposite(), WinType::Time);
+        }
+
+        // Checkmate or stalemate with no legal moves for side to move
+
+        if self.legal_moves().is_empty() {
+            if self.in_check() {
+                return GameResult::Win(self.turn.get_opposite(), WinType::Checkmate);
             }
             return GameResult::Draw(DrawType::Stalemate);
         }
 
+        // Check for insufficient material
+
+        if self.has_insufficient_material() {
+            return GameResult::Draw(DrawType::Material);
+        }
+
+        // Check for fivefold repetition or threefold if automatic unless repetition rule is off
+
+        let repetitions = if self.automatic_threefold { CLAIMABLE_REPETITIONS } else { AUTOMATIC_REPETITIONS };
+        if self.repetition_rule && self.zobrist_table.get().copied().unwrap_or(0) >= repetitions {
+            return GameResult::Draw(DrawType::Repetition);
+        }
+
+        // Check for 50 move rule (100 plies) unless turned off
+
+        if self.fifty_move_rule && self.last_active_ply >= 100 {
+            return GameResult::Draw(DrawType::FiftyMove);
+        }
+
+        // Check for claimed draw
+
+        if let Some(draw) = self.claimed_draw {
+            return GameResult::Draw(draw);
+        }
+
         // No result yet
 
         GameResult::None
     }
 
-    // Print board position to terminal
+    // Get draw that can be claimed but isn't automatic
+
+    pub fn claimable_draw(&self) -> Option<DrawType> {
+        if self.repetition_rule && self.zobrist_table.get().copied().unwrap_or(0) >= CLAIMABLE_REPETITIONS {
+            return Some(DrawType::Repetition);
+        }
+        if self.is_dead_position() {
+            return Some(DrawType::Material);
+        }
+        None
+    }
+
+    // Get total material value for white and black excluding kings
+
+    pub fn material(&self) -> (i32, i32) {
+        let mut material = (0, 0);
+        for square in self.board.iter().flatten() {
+            if let Square::Full(piece) = square {
+                if piece.typ == King {
+                    continue;
+                }
+                match piece.side {
+                    White => material.0 += piece.typ.value(),
+                    Black => material.1 += piece.typ.value()
+                }
+            }
+        }
+        material
+    }
+
+    // Get pieces captured by both sides in moves played in order of capture
+    // Promotions don't count as captures, so promoted pieces never make counts go negative
+
+    pub fn captured_pieces(&self) -> Vec<Piece> {
+        self.history.iter()
+            .filter_map(|record| record.captured)
+            .collect()
+    }
+
+    // Get pieces captured by side in moves played in order of capture
+
+    pub fn captures(&self, side: Side) -> Vec<Piece> {
+        self.history.iter()
+            .filter_map(|record| record.captured)
+            .filter(|piece| piece.side != side)
+            .collect()
+    }
+
+    // Check if neither side can checkmate with any sequence of moves
+
+    pub fn has_insufficient_material(&self) -> bool {
+        let (white, black) = match self.get_minor_pieces() {
+            Some(pieces) => pieces,
+            None => return false
+        };
+        match (white.as_slice(), black.as_slice()) {
+            // King against king or king and minor piece
+
+            ([], []) | ([_], []) | ([], [_]) => true,
+
+            // Kings and bishops on same color squares
+
+            ([(Bishop, white_color)], [(Bishop, black_color)]) => white_color == black_color,
+            _ => false
+        }
+    }
+
+    // Check for insufficient material or positions where mate can't be forced
+
+    pub fn is_dead_position(&self) -> bool {
+        if self.has_insufficient_material() {
+            return true;
+        }
+
+        // King and two knights against king
+
+        let (white, black) = match self.get_minor_pieces() {
+            Some(pieces) => pieces,
+            None => return false
+        };
+        let two_knights = |pieces: &[(PieceType, usize)]| matches!(pieces, [(Knight, _), (Knight, _)]);
+        (two_knights(&white) && black.is_empty()) || (white.is_empty() && two_knights(&black))
+    }
+
+    // Get minor pieces for each side with square color or none if pawns or major pieces remain
+
+    #[allow(clippy::type_complexity)]
+    fn get_minor_pieces(&self) -> Option<(Vec<(PieceType, usize)>, Vec<(PieceType, usize)>)> {
+        let mut white = vec![];
+        let mut black = vec![];
+        for (r, row) in self.board.iter().enumerate() {
+            for (c, square) in row.iter().enumerate() {
+                if let Square::Full(piece) = square {
+                    match piece.typ {
+                        King => (),
+                        Knight | Bishop => match piece.side {
+                            White => white.push((piece.typ, (r + c) % 2)),
+                            Black => black.push((piece.typ, (r + c) % 2))
+                        },
+                        Pawn | Rook | Queen => return None
+                    }
+                }
+            }
+        }
+        Some((white, black))
+    }
+
+    // Print board position to terminal from side to move
 
     pub fn display(&self) {
-        // Get row and column ranges from active turn
+        self.display_from(self.turn);
+    }
+
+    // Print board position to terminal with side at the bottom
 
-        let (row_range, col_range): (Vec<usize>, Vec<usize>) = match self.turn {
+    pub fn display_from(&self, side: Side) {
+        print!("{}", self.render_from(side, true));
+    }
+
+    // Get board position text from side to move with colors
+
+    pub fn render(&self) -> String {
+        self.render_from(self.turn, true)
+    }
+
+    // Get board position text with side at the bottom, with or without colors
+    // Colors are also left out when colored output is disabled
+
+    pub fn render_from(&self, side: Side, color: bool) -> String {
+        // Add clock header for timed games
+
+        let mut output = String::new();
+        if let Some(clock) = self.clock {
+            output.push_str(&format!("{clock}\n\n"));
+        }
+
+        // Get row and column ranges from side
+
+        let (row_range, col_range): (Vec<usize>, Vec<usize>) = match side {
             White => ((0..SIZE).rev().collect(), (0..SIZE).collect()),
             Black => ((0..SIZE).collect(), (0..SIZE).rev().collect())
         };
 
-        // Print top columns labels
+        // Add top columns labels
 
-        print!("  ");
+        let mut lines = vec![];
+        let mut line = String::from("  ");
         let char_start = 'a' as usize;
         for c in &col_range {
-            print!("{} ", (char_start + c) as u8 as char);
+            line.push_str(&format!("{} ", (char_start + c) as u8 as char));
         }
-        println!();
+        lines.push(line);
 
-        // Print board squares and row labels
+        // Add board squares and row labels
 
-        let char_start = 'a' as usize;
         for r in &row_range {
-            print!("{} ", r + 1);
+            let mut line = format!("{} ", r + 1);
             for c in &col_range {
                 // Color square with piece and background
 
-                let square = format!("{} ", self.board[*r][*c].get_char());
+                let square = match self.board[*r][*c] {
+                    Square::Full(piece) if util::letter_pieces() => format!("{} ", piece.get_letter()),
+                    square => format!("{} ", square.get_char())
+                };
+                if !color {
+                    line.push_str(&square);
+                    continue;
+                }
+
                 let square = match self.board[*r][*c] {
                     Square::Full(piece) => match piece.side {
                         White => square.truecolor(255, 255, 255),
@@ -283,28 +1107,214 @@ impl Game {
                 };
 
                 if r % 2 == c % 2 {
-                    print!("{}", square.on_truecolor(57, 57, 57));
+                    line.push_str(&square.on_truecolor(57, 57, 57).to_string());
                 } else {
-                    print!("{}", square.on_truecolor(75, 75, 75));
+                    line.push_str(&square.on_truecolor(75, 75, 75).to_string());
                 }
             }
-            println!(" {}", r + 1);
+            line.push_str(&format!(" {}", r + 1));
+            lines.push(line);
         }
 
-        // Print bottom columns labels
+        // Add bottom columns labels
 
-        print!("  ");
+        let mut line = String::from("  ");
         for c in &col_range {
-            print!("{} ", (char_start + c) as u8 as char);
+            line.push_str(&format!("{} ", (char_start + c) as u8 as char));
+        }
+        lines.push(line);
+
+        // Add numbered move transcript beside board if it fits in terminal width
+
+        let transcript = if util::show_transcript() && !self.history.is_empty() {
+            self.transcript()
+        } else {
+            vec![]
+        };
+        let beside = match util::terminal_width() {
+            Some(width) if !transcript.is_empty() => Game::layout_transcript(&transcript, lines.len(), width),
+            _ => None
+        };
+        for (index, line) in lines.into_iter().enumerate() {
+            output.push_str(&line);
+            if let Some(moves) = beside.as_ref().and_then(|beside| beside.get(index)) {
+                // Pad column label lines out to width of row lines
+
+                let padding = if index == 0 || index == SIZE + 1 { 2 } else { 0 };
+                output.push_str(&" ".repeat(padding + TRANSCRIPT_GAP));
+                output.push_str(moves);
+            }
+            output.push('\n');
+        }
+
+        // Add en passant target when capture is possible
+
+        if let Some(target) = self.en_passant_target() {
+            output.push_str(&format!("en passant: {target}\n"));
+        }
+
+        // Add pieces captured by each side
+
+        if util::show_captures() {
+            for capturer in [White, Black] {
+                let captures: Vec<String> = self.captures(capturer)
+                    .into_iter()
+                    .map(|piece| if util::letter_pieces() {
+                        piece.get_letter().to_string()
+                    } else {
+                        piece.get_char().to_string()
+                    })
+                    .collect();
+                output.push_str(&format!("\nCaptured by {}: {}", capturer.name().to_lowercase(), captures.join(" ")));
+            }
+            output.push('\n');
+        }
+
+        // Add numbered move transcript wrapped by line below board when too narrow or width is unknown
+
+        if beside.is_none() && !transcript.is_empty() {
+            output.push('\n');
+            for line in transcript.chunks(TRANSCRIPT_MOVES_PER_LINE) {
+                output.push_str(&format!("{}\n", line.join("  ")));
+            }
+        }
+        output
+    }
+
+    // Get transcript lines to print beside board using fewest moves per line that fit in board height
+    // Returns none if transcript cannot fit beside board in terminal width
+
+    fn layout_transcript(transcript: &[String], height: usize, width: usize) -> Option<Vec<String>> {
+        let available = width.checked_sub(BOARD_WIDTH + TRANSCRIPT_GAP)?;
+        let per_line = transcript.len().div_ceil(height).max(1);
+        let lines: Vec<String> = transcript.chunks(per_line).map(|line| line.join("  ")).collect();
+        if lines.iter().all(|line| line.chars().count() <= available) {
+            Some(lines)
+        } else {
+            None
+        }
+    }
+
+    // Get number of plies played from starting position
+
+    pub fn ply_count(&self) -> usize {
+        self.history.len()
+    }
+
+    // Get move number and side to move for ply index from starting position
+
+    pub fn get_move_number(&self, ply: usize) -> (u32, Side) {
+        let offset = if self.start_turn == White { 0 } else { 1 };
+        let number = self.start_fullmove + ((ply + offset) / 2) as u32;
+        let side = if ply.is_multiple_of(2) { self.start_turn } else { self.start_turn.get_opposite() };
+        (number, side)
+    }
+
+    // Get starting position and moves played from it in order
+
+    pub fn start_and_moves(&self) -> (Game, Vec<(Move, Option<PieceType>)>) {
+        let mut game = self.clone();
+        let mut moves = vec![];
+        while let Some(record) = game.history.last() {
+            moves.push((record.mov, record.promotion));
+            game.undo();
+        }
+        moves.reverse();
+        (game, moves)
+    }
+
+    // Get PGN with result, starting FEN for games not from the initial position, and numbered SAN moves
+
+    pub fn to_pgn(&self) -> String {
+        // Replay moves from starting position
+
+        let (mut game, moves) = self.start_and_moves();
+        let result = self.clone().get_game_result().to_pgn();
+        let mut pgn = format!("[Result \"{result}\"]\n");
+        let fen = game.to_fen();
+        if fen != Game::new().to_fen() {
+            pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n"));
+        }
+        pgn.push('\n');
+
+        let mut movetext = vec![];
+        for (ply, (mov, promotion)) in moves.into_iter().enumerate() {
+            let san = move_input::to_san(&mut game, mov, promotion);
+            match self.get_move_number(ply) {
+                (number, White) => movetext.push(format!("{number}. {san}")),
+                (number, Black) if ply == 0 => movetext.push(format!("{number}... {san}")),
+                _ => movetext.push(san)
+            }
+            if game.make_move(mov, promotion).is_err() {
+                break;
+            }
+        }
+        movetext.push(String::from(result));
+        pgn.push_str(&movetext.join(" "));
+        pgn
+    }
+
+    // Get numbered SAN transcript of moves played with one entry per move number
+
+    pub fn transcript(&self) -> Vec<String> {
+        // Replay moves from first recorded position
+
+        let (mut game, moves) = self.start_and_moves();
+        let mut transcript = vec![];
+        for (mov, promotion) in moves {
+            let san = move_input::to_san(&mut game, mov, promotion);
+            match game.turn {
+                White => transcript.push(format!("{}. {san}", game.fullmove)),
+                Black => match transcript.last_mut() {
+                    Some(entry) => entry.push_str(&format!(" {san}")),
+                    None => transcript.push(format!("{}... {san}", game.fullmove))
+                }
+            }
+            if game.make_move(mov, promotion).is_err() {
+                break;
+            }
+        }
+        transcript
+    }
+}
+
+// Get rook position and castled king and rook positions for castle direction
+
+fn get_castle_squares(side: Side, dir: CastleDirection) -> (Point, Point, Point) {
+    match side {
+        White => match dir {
+            CastleDirection::A => (
+                INITIAL_POSITIONS.WHITE_ROOKS.0,
+                CASTLED_POSITIONS.WHITE_KING.0,
+                CASTLED_POSITIONS.WHITE_ROOKS.0
+            ),
+            CastleDirection::H => (
+                INITIAL_POSITIONS.WHITE_ROOKS.1,
+                CASTLED_POSITIONS.WHITE_KING.1,
+                CASTLED_POSITIONS.WHITE_ROOKS.1
+            )
+        },
+        Black => match dir {
+            CastleDirection::A => (
+                INITIAL_POSITIONS.BLACK_ROOKS.0,
+                CASTLED_POSITIONS.BLACK_KING.0,
+                CASTLED_POSITIONS.BLACK_ROOKS.0
+            ),
+            CastleDirection::H => (
+                INITIAL_POSITIONS.BLACK_ROOKS.1,
+                CASTLED_POSITIONS.BLACK_KING.1,
+                CASTLED_POSITIONS.BLACK_ROOKS.1
+            )
         }
-        println!();
     }
 }
 
 // Generate possible moves given move input
 
-fn get_possible_moves(move_data: PlayerMove, game: &Game) -> Vec<Move> {
+fn get_possible_moves(move_data: PlayerMove, game: &Game) -> Result<Vec<Move>, IllegalMove> {
     let mut possible = vec![];
+    let mut blocked = false;
+    let mut wrong_side = false;
 
     for r in 0..SIZE {
         for c in 0..SIZE {
@@ -320,6 +1330,7 @@ fn get_possible_moves(move_data: PlayerMove, game: &Game) -> Vec<Move> {
                 if piece.side == game.turn && piece.typ == move_data.piece {
                     // Filter moves by to square
 
+                    let before = possible.len();
                     for mov in get_moves(piece, r, c, game) {
                         if mov == move_data.to {
                             possible.push(Move {
@@ -328,23 +1339,472 @@ fn get_possible_moves(move_data: PlayerMove, game: &Game) -> Vec<Move> {
                             });
                         }
                     }
+
+                    // Check if piece is blocked from reaching square
+
+                    if possible.len() == before && !blocked {
+                        blocked = is_blocked(piece, Point(r, c), move_data.to, game);
+                    }
+                } else if piece.typ == move_data.piece && !wrong_side {
+                    // Check if opponent piece can reach square
+
+                    wrong_side = get_moves(piece, r, c, game).contains(&move_data.to);
                 }
             }
         }
     }
 
-    possible
+    if !possible.is_empty() {
+        Ok(possible)
+    } else if blocked {
+        Err(IllegalMove::Blocked)
+    } else if wrong_side {
+        Err(IllegalMove::WrongSide)
+    } else {
+        Err(IllegalMove::Unreachable)
+    }
+}
+
+// Check if piece could reach square with other pieces removed
+
+fn is_blocked(piece: Piece, from: Point, to: Point, game: &Game) -> bool {
+    // Clear board except for moving piece and target square
+
+    let mut board = [[Square::Empty; SIZE]; SIZE];
+    board[from.0][from.1] = game.board[from.0][from.1];
+    board[to.0][to.1] = game.board[to.0][to.1];
+
+    get_board_moves(piece, from.0, from.1, &board, game.pawn_double_moved).contains(&to)
 }
 
 // Generate moves for piece
 
 fn get_moves(piece: Piece, row: usize, col: usize, game: &Game) -> Vec<Point> {
+    get_board_moves(piece, row, col, &game.board, game.pawn_double_moved)
+}
+
+// Generate moves for piece on board
+
+fn get_board_moves(
+    piece: Piece,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    pawn_double_moved: Option<Point>
+) -> Vec<Point> {
+    let mut moves = vec![];
+    push_board_moves(piece, row, col, board, pawn_double_moved, &mut moves);
+    moves
+}
+
+// Push moves for piece on board into buffer
+
+fn push_board_moves(
+    piece: Piece,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    pawn_double_moved: Option<Point>,
+    moves: &mut Vec<Point>
+) {
     match piece.typ {
-        Pawn => moves::get_pawn_moves(piece.side, row, col, &game.board, game.pawn_double_moved),
-        Knight => moves::get_knight_moves(piece.side, row, col, &game.board),
-        Bishop => moves::get_bishop_moves(piece.side, row, col, &game.board),
-        Rook => moves::get_rook_moves(piece.side, row, col, &game.board),
-        Queen => moves::get_queen_moves(piece.side, row, col, &game.board),
-        King => moves::get_king_moves(piece.side, row, col, &game.board)
+        Pawn => moves::push_pawn_moves(piece.side, row, col, board, pawn_double_moved, moves),
+        Knight => moves::push_knight_moves(piece.side, row, col, board, moves),
+        Bishop => moves::push_bishop_moves(piece.side, row, col, board, moves),
+        Rook => moves::push_rook_moves(piece.side, row, col, board, moves),
+        Queen => moves::push_queen_moves(piece.side, row, col, board, moves),
+        King => moves::push_king_moves(piece.side, row, col, board, moves)
+    }
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+
+    fn mov(input: &str) -> Move {
+        move_input::parse_coordinates(input).unwrap().0
+    }
+
+    fn play(game: &mut Game, input: &str) -> Result<(), MoveError> {
+        let move_data = move_input::parse_move(input)?;
+        game.apply_player_move(input, move_data)
+    }
+
+    // Back rank mate is a win for side delivering mate
+
+    #[test]
+    fn back_rank_mate() {
+        let mut game = Game::from_fen("3R2k1/5ppp/8/8/8/8/8/6K1 b - - 1 1").unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Win(White, WinType::Checkmate));
+    }
+
+    // Side to move with no legal moves and not in check is stalemated
+
+    #[test]
+    fn stalemate() {
+        let mut game = Game::from_fen("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::Stalemate));
+
+        let mut game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::Stalemate));
+
+        // Side to move with a pawn move isn't stalemated
+
+        let mut game = Game::from_fen("7k/5Q2/6K1/8/p7/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.get_game_result(), GameResult::None);
+    }
+
+    // Perft node counts match reference counts for standard test positions
+
+    #[test]
+    fn perft_reference_counts() {
+        let positions: [(&str, &[u64]); 5] = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281]),
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]),
+            ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379])
+        ];
+        for (fen, counts) in positions {
+            let mut game = Game::from_fen(fen).unwrap();
+            for (depth, count) in counts.iter().enumerate() {
+                assert_eq!(game.perft(depth as u32 + 1), *count, "perft {} of {fen}", depth + 1);
+            }
+            assert_eq!(game.to_fen(), fen);
+        }
+    }
+
+    // Perft divide is sorted by coordinate move and matches reference counts for each root move
+
+    #[test]
+    fn perft_divide_start_position() {
+        let mut game = Game::new();
+        let divide: Vec<(String, u64)> = game.perft_divide(3)
+            .into_iter()
+            .map(|(mov, promotion, count)| (move_input::to_coordinates(mov, promotion), count))
+            .collect();
+        let expected = [
+            ("a2a3", 380), ("a2a4", 420), ("b1a3", 400), ("b1c3", 440), ("b2b3", 420),
+            ("b2b4", 421), ("c2c3", 420), ("c2c4", 441), ("d2d3", 539), ("d2d4", 560),
+            ("e2e3", 599), ("e2e4", 600), ("f2f3", 380), ("f2f4", 401), ("g1f3", 440),
+            ("g1h3", 400), ("g2g3", 420), ("g2g4", 421), ("h2h3", 380), ("h2h4", 420)
+        ];
+        let expected: Vec<(String, u64)> = expected.iter().map(|(mov, count)| (String::from(*mov), *count)).collect();
+        assert_eq!(divide, expected);
+        assert_eq!(game.perft_divide(0), vec![]);
+    }
+
+    // Perft divide of a midgame position lists every root move and sums to the reference count
+
+    #[test]
+    fn perft_divide_kiwipete() {
+        let mut game = Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let divide = game.perft_divide(3);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, _, count)| count).sum::<u64>(), 97862);
+        let knight = divide.iter().find(|(mov, _, _)| mov.to_string() == "c3b1").unwrap();
+        assert_eq!(knight.2, 2038);
+    }
+
+    // Legal move predicate checks piece, destination, and king safety without changing game
+
+    #[test]
+    fn is_legal_move() {
+        let mut game = Game::new();
+        let fen = game.to_fen();
+        let hash = game.hash();
+        assert!(game.is_legal_move(mov("e2e4")));
+        assert!(game.is_legal_move(mov("g1f3")));
+        assert!(!game.is_legal_move(mov("e2e5")));
+        assert!(!game.is_legal_move(mov("e7e5")));
+        assert!(!game.is_legal_move(mov("e3e4")));
+        assert!(!game.is_legal_move(mov("e1g1")));
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.hash(), hash);
+        assert!(game.history.is_empty());
+
+        // Pinned pieces, castling, and en passant
+
+        let mut game = Game::from_fen("4k3/4r3/8/2Pp4/8/8/4B3/R3K2R w KQ d6 0 1").unwrap();
+        let fen = game.to_fen();
+        assert!(!game.is_legal_move(mov("e2d3")));
+        assert!(game.is_legal_move(mov("e1c1")));
+        assert!(game.is_legal_move(mov("e1g1")));
+        assert!(game.is_legal_move(mov("c5d6")));
+        assert!(!game.is_legal_move(mov("e2e3")));
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    // Promotion places chosen piece for every promotion piece, including capture promotions
+
+    #[test]
+    fn underpromotion() {
+        for (letter, typ) in [('N', Knight), ('B', Bishop), ('R', Rook), ('Q', Queen)] {
+            let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            play(&mut game, &format!("a8={letter}")).unwrap();
+            assert_eq!(game.board[7][0], Square::full(White, typ));
+            assert_eq!(game.board[6][0], Square::Empty);
+
+            let mut game = Game::from_fen("3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+            play(&mut game, &format!("exd8={letter}")).unwrap();
+            assert_eq!(game.board[7][3], Square::full(White, typ));
+            assert_eq!(game.board[6][4], Square::Empty);
+
+            let mut game = Game::from_fen("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1").unwrap();
+            play(&mut game, &format!("bxa1={letter}")).unwrap();
+            assert_eq!(game.board[0][0], Square::full(Black, typ));
+        }
+    }
+
+    // Promotion to king or pawn, or missing promotion piece, is rejected
+
+    #[test]
+    fn invalid_promotion() {
+        assert!(move_input::parse_move("a8=K").is_err());
+        assert!(move_input::parse_move("a8=P").is_err());
+        assert!(move_input::parse_move("bxa8=K").is_err());
+
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(game.make_move(mov("a7a8"), Some(King)).is_err());
+        assert!(game.make_move(mov("a7a8"), Some(Pawn)).is_err());
+        assert!(game.make_move(mov("a7a8"), None).is_err());
+        assert!(matches!(play(&mut game, "a8"), Err(MoveError::MissingPromotion(_))));
+        assert_eq!(game.board[6][0], Square::full(White, Pawn));
+    }
+
+    // Coordinate move of an opponent piece reports the piece isn't yours to move
+
+    #[test]
+    fn coordinate_move_wrong_side() {
+        let mut game = Game::new();
+        assert_eq!(
+            game.validate_coordinate_move("e7e5", mov("e7e5")),
+            Err(MoveError::Illegal(String::from("e7e5"), Pawn, IllegalMove::WrongSide))
+        );
+        assert_eq!(
+            game.validate_coordinate_move("e7e5", mov("e7e5")).unwrap_err().to_string(),
+            "'e7e5' isn't a valid move, that's not your piece to move, please try again"
+        );
+        assert_eq!(game.validate_coordinate_move("e2e5", mov("e2e5")), Err(MoveError::NotLegal(String::from("e2e5"))));
+        assert_eq!(game.validate_coordinate_move("e3e4", mov("e3e4")), Err(MoveError::NotLegal(String::from("e3e4"))));
+        assert_eq!(game.validate_coordinate_move("e2e4", mov("e2e4")), Ok(()));
+
+        game.make_move(mov("e2e4"), None).unwrap();
+        assert_eq!(
+            game.validate_coordinate_move("g1f3", mov("g1f3")),
+            Err(MoveError::Illegal(String::from("g1f3"), Knight, IllegalMove::WrongSide))
+        );
+    }
+
+    // En passant capture that exposes the king along the rank is illegal
+
+    #[test]
+    fn en_passant_discovered_check() {
+        let mut game = Game::from_fen("8/2p5/8/KP5r/8/8/8/4k3 b - - 0 1").unwrap();
+        game.make_move(mov("c7c5"), None).unwrap();
+        let board = game.board;
+        assert!(!game.legal_moves().iter().any(|(legal, _)| *legal == mov("b5c6")));
+        assert!(!game.is_legal_move(mov("b5c6")));
+        assert!(game.board == board);
+
+        // Same capture is legal when the king isn't on the rank
+
+        let mut game = Game::from_fen("8/2p5/8/1P5r/K7/8/8/4k3 b - - 0 1").unwrap();
+        game.make_move(mov("c7c5"), None).unwrap();
+        assert!(game.legal_moves().iter().any(|(legal, _)| *legal == mov("b5c6")));
+    }
+
+    // Material sums and captured pieces follow moves played, including promotions
+
+    #[test]
+    fn material_and_captures() {
+        let mut game = Game::new();
+        assert_eq!(game.material(), (4000, 4000));
+        assert!(game.captured_pieces().is_empty());
+
+        for input in ["e4", "d5", "exd5", "Qxd5"] {
+            play(&mut game, input).unwrap();
+        }
+        assert_eq!(game.material(), (3900, 3900));
+        assert_eq!(game.captured_pieces(), vec![Piece { side: Black, typ: Pawn }, Piece { side: White, typ: Pawn }]);
+        assert_eq!(game.captures(White), vec![Piece { side: Black, typ: Pawn }]);
+
+        // Promoted pawn isn't counted as captured
+
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        play(&mut game, "a8=Q").unwrap();
+        assert_eq!(game.material(), (900, 0));
+        assert!(game.captured_pieces().is_empty());
+
+        let mut game = Game::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        play(&mut game, "axb8=Q").unwrap();
+        assert_eq!(game.captured_pieces(), vec![Piece { side: Black, typ: Knight }]);
+    }
+
+    // Starting FEN position counts once so the threefold triggers on its third total occurrence
+
+    #[test]
+    fn fen_threefold_repetition() {
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        assert_eq!(game.zobrist_table.get().copied(), Some(1));
+
+        // Play rook and king back and forth, returning to the start position after each cycle
+
+        let cycle = |game: &mut Game| -> Vec<GameResult> {
+            ["h1h2", "e8d8", "h2h1", "d8e8"].into_iter().map(|input| {
+                game.make_move(mov(input), None).unwrap();
+                game.get_game_result()
+            }).collect()
+        };
+
+        let mut automatic = game.clone();
+        automatic.automatic_threefold = true;
+        assert_eq!(cycle(&mut automatic), vec![GameResult::None; 4]);
+        assert_eq!(cycle(&mut automatic)[3], GameResult::Draw(DrawType::Repetition));
+        assert_eq!(automatic.zobrist_table.get().copied(), Some(3));
+
+        let mut claimed = game.clone();
+        cycle(&mut claimed);
+        assert_eq!(claimed.claimable_draw(), None);
+        cycle(&mut claimed);
+        assert_eq!(claimed.claimable_draw(), Some(DrawType::Repetition));
+    }
+
+    // En passant is illegal when it reveals a rank attack or moves a diagonally pinned pawn off the pin
+
+    #[test]
+    fn en_passant_pins() {
+        let is_legal = |fen: &str, input: &str| {
+            let mut game = Game::from_fen(fen).unwrap();
+            let legal = game.legal_moves().iter().any(|(legal, _)| *legal == mov(input));
+            assert_eq!(game.is_legal_move(mov(input)), legal);
+            legal
+        };
+
+        // Both pawns leave the rank between king and rook or queen
+
+        assert!(!is_legal("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 2", "b5c6"));
+        assert!(is_legal("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 2", "b5b6"));
+        assert!(!is_legal("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1", "e4d3"));
+
+        // Pawn pinned along a diagonal can only capture along the pin
+
+        assert!(!is_legal("8/8/4B3/8/2pP4/1k6/8/7K b - d3 0 1", "c4d3"));
+        assert!(is_legal("8/8/8/1k6/2pP4/8/4B3/7K b - d3 0 1", "c4d3"));
+    }
+
+    // Checkmate takes precedence over repetition and fifty move draws
+
+    #[test]
+    fn mate_beats_draws() {
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80").unwrap();
+        game.make_move(mov("a1a8"), None).unwrap();
+        assert_eq!(game.last_active_ply, 100);
+        game.zobrist_table.insert(5);
+        game.claimed_draw = Some(DrawType::Repetition);
+        assert_eq!(game.get_game_result(), GameResult::Win(White, WinType::Checkmate));
+
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80").unwrap();
+        game.make_move(mov("a1b1"), None).unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::FiftyMove));
+
+        // Halfmove clocks past the limit from FEN are still draws
+
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 120 80").unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::FiftyMove));
+        game.make_move(mov("a1b1"), None).unwrap();
+        assert_eq!(game.last_active_ply, 121);
+        assert_eq!(game.get_game_result(), GameResult::Draw(DrawType::FiftyMove));
+        game.undo();
+        game.make_move(mov("a1a8"), None).unwrap();
+        assert_eq!(game.get_game_result(), GameResult::Win(White, WinType::Checkmate));
+    }
+
+    // Castle both ways for both sides and undo
+
+    fn check_castle(fen: &str, input: &str, side: Side, king: Point, rook: Point, old_rook: Point) {
+        let mut game = Game::from_fen(fen).unwrap();
+        let castle = mov(input);
+        assert!(game.legal_moves().contains(&(castle, None)), "{input}");
+        game.make_move(castle, None).unwrap();
+        assert_eq!(game.board[king.0][king.1], Square::full(side, King));
+        assert_eq!(game.board[rook.0][rook.1], Square::full(side, Rook));
+        assert_eq!(game.board[castle.from.0][castle.from.1], Square::Empty);
+        assert_eq!(game.board[old_rook.0][old_rook.1], Square::Empty);
+        let (king_position, rights) = match side {
+            White => (game.king_positions.white, game.castle_rights.white),
+            Black => (game.king_positions.black, game.castle_rights.black)
+        };
+        assert_eq!(king_position, king);
+        assert_eq!(rights, (false, false));
+        game.undo().unwrap();
+        assert_eq!(game.castle_rights.white, (true, true));
+        assert_eq!(game.castle_rights.black, (true, true));
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    #[test]
+    fn castle_all_four() {
+        let white = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let black = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+        check_castle(white, "e1c1", White, CASTLED_POSITIONS.WHITE_KING.0, CASTLED_POSITIONS.WHITE_ROOKS.0, INITIAL_POSITIONS.WHITE_ROOKS.0);
+        check_castle(white, "e1g1", White, CASTLED_POSITIONS.WHITE_KING.1, CASTLED_POSITIONS.WHITE_ROOKS.1, INITIAL_POSITIONS.WHITE_ROOKS.1);
+        check_castle(black, "e8c8", Black, CASTLED_POSITIONS.BLACK_KING.0, CASTLED_POSITIONS.BLACK_ROOKS.0, INITIAL_POSITIONS.BLACK_ROOKS.0);
+        check_castle(black, "e8g8", Black, CASTLED_POSITIONS.BLACK_KING.1, CASTLED_POSITIONS.BLACK_ROOKS.1, INITIAL_POSITIONS.BLACK_ROOKS.1);
+    }
+
+    // Capturing rook on its starting square removes castle right for both sides
+
+    #[test]
+    fn capture_rook_clears_castle_right() {
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        game.make_move(mov("h1h8"), None).unwrap();
+        assert_eq!(game.castle_rights.black, (true, false));
+        assert_eq!(game.castle_rights.white, (true, false));
+        assert!(!game.legal_moves().contains(&(mov("e8g8"), None)));
+        game.undo().unwrap();
+        assert_eq!(game.castle_rights.black, (true, true));
+        assert_eq!(game.castle_rights.white, (true, true));
+    }
+
+    // Promotion on the side's own back rank is rejected
+
+    #[test]
+    fn wrong_promotion_rank() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/P7/4K3 w - - 0 1").unwrap();
+        let error = play(&mut game, "a1=Q").unwrap_err();
+        assert!(matches!(error, MoveError::WrongPromotionRank(_, White)), "{error:?}");
+        assert_eq!(error.to_string(), "'a1=Q' promotes on the wrong rank, white pawns promote on rank 8, please try again");
+
+        let mut game = Game::from_fen("4k3/p7/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let error = play(&mut game, "a8=Q").unwrap_err();
+        assert!(matches!(error, MoveError::WrongPromotionRank(_, Black)), "{error:?}");
+        assert_eq!(error.to_string(), "'a8=Q' promotes on the wrong rank, black pawns promote on rank 1, please try again");
+
+        let mut game = Game::from_fen("R3k3/1p6/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let error = play(&mut game, "bxa8=Q").unwrap_err();
+        assert!(matches!(error, MoveError::WrongPromotionRank(_, Black)), "{error:?}");
+    }
+
+    // PGN from position numbers moves from starting fullmove and loads back
+
+    #[test]
+    fn pgn_move_numbers() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 20";
+        let mut game = Game::from_fen(fen).unwrap();
+        for input in ["e8d7", "e2e4", "d7e6", "e1e2"] {
+            game.make_move(mov(input), None).unwrap();
+        }
+        assert_eq!(game.ply_count(), 4);
+        let pgn = game.to_pgn();
+        assert!(pgn.contains(&format!("[FEN \"{fen}\"]")), "{pgn}");
+        assert!(pgn.ends_with("20... Kd7 21. e4 Ke6 22. Ke2 *"), "{pgn}");
+        assert_eq!(Game::from_pgn(&pgn).unwrap().to_fen(), game.to_fen());
+
+        let mut game = Game::new();
+        for input in ["e2e4", "e7e5"] {
+            game.make_move(mov(input), None).unwrap();
+        }
+        assert_eq!(game.to_pgn(), "[Result \"*\"]\n\n1. e4 e5 *");
     }
 }
\ No newline at end of file
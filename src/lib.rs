@@ -0,0 +1,12 @@
+// Modules
+
+pub mod board;
+pub mod moves;
+pub mod zobrist;
+pub mod game;
+pub mod clock;
+pub mod engine;
+pub mod move_input;
+pub mod util;
+pub mod bench;
+pub mod uci;
\ No newline at end of file
@@ -1,40 +1,98 @@
 // Imports
 
-mod board;
-mod moves;
-mod zobrist;
-mod game;
-mod engine;
-mod move_input;
-mod util;
-
-use crate::board::Side::{ White, Black };
-use crate::board::Game;
-use crate::game::{ GameResult, DrawType };
+use bad_chess::board::Side::{ self, White, Black };
+use bad_chess::board::SIZE;
+use bad_chess::board::{ Square, Point, Piece };
+use bad_chess::board::PieceType::{ self, Knight, Bishop, Rook, Queen };
+use bad_chess::board::{ Game, CastleRights };
+use bad_chess::game::{ GameResult, WinType, DrawType, MoveError, PGN_RESULTS };
+use bad_chess::clock::Clock;
+use bad_chess::engine::{ self, Difficulty, EvalParams, SearchStats };
+use bad_chess::engine::book::Book;
+use bad_chess::move_input;
+use bad_chess::util;
+use bad_chess::bench;
+use bad_chess::uci;
 
+use std::env;
+use std::fs;
 use std::io;
+use std::process;
 use colored::Colorize;
+use rand_chacha::ChaCha8Rng;
+
+// Analysis parameters
+
+const ANALYSIS_MOVES: usize = 5;
 
 // Run bad chess
 
 fn main() {
+    // Run subcommands
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let verbose = args.iter().any(|arg| arg == "--verbose");
+    let no_color = args.iter().any(|arg| arg == "--no-color");
+    let letters = args.iter().any(|arg| arg == "--letters");
+    let auto_threefold = args.iter().any(|arg| arg == "--auto-threefold");
+    let no_fifty_move = args.iter().any(|arg| arg == "--no-fifty-move");
+    let no_repetition = args.iter().any(|arg| arg == "--no-repetition");
+    let transcript = args.iter().any(|arg| arg == "--transcript");
+    let captures = args.iter().any(|arg| arg == "--captures");
+    args.retain(|arg| !["--verbose", "--no-color", "--letters", "--auto-threefold", "--no-fifty-move", "--no-repetition", "--transcript", "--captures"].contains(&arg.as_str()));
+    util::configure_color(no_color);
+    util::set_letter_pieces(letters);
+    util::set_show_transcript(transcript);
+    util::set_show_captures(captures);
+
+    // Get number of search threads
+
+    let mut threads = 1;
+    if let Some(i) = args.iter().position(|arg| arg == "--threads") {
+        threads = match args.get(i + 1).map(|count| count.parse()) {
+            Some(Ok(count)) if count > 0 => count,
+            _ => {
+                eprintln!("Usage: --threads <count>");
+                return;
+            }
+        };
+        args.drain(i..i + 2);
+    }
+
+    if let Some(command) = args.first() {
+        match command.as_str() {
+            "bench" => bench::run_benchmark(),
+            "uci" => uci::run_uci(),
+            "replay" => run_replay(),
+            "view" => run_viewer(&args[1..]),
+            "--perft" => run_perft(&args[1..]),
+            "tournament" => run_tournament(&args[1..]),
+            _ => eprintln!("Unknown command '{command}', expected 'bench', 'uci', 'replay', 'view <pgn file>', '--perft <depth> [fen]', 'tournament <games> <depth> [seed]', '--verbose', '--no-color', '--letters', '--auto-threefold', '--no-fifty-move', '--no-repetition', '--transcript', '--captures', or '--threads <count>'")
+        }
+        return;
+    }
+
     // Run game loop
 
     loop {
         // Get input game mode
 
         display_home_screen();
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            util::clear_screen();
-            println!("{}", "Error reading input, please try again".bold());
-            continue;
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}", error.bold());
+                continue;
+            }
+        };
+        if util::is_quit(&input) {
+            break;
         }
 
-        let input = input.trim();
         let input: u32 = match input.parse() {
             Ok(value) => {
-                if value != 1 && value != 2 {
+                if !(1..=4).contains(&value) {
                     util::clear_screen();
                     println!("{}", format!("'{input}' isn't a valid game mode, please try again").bold());
                     continue;
@@ -50,10 +108,15 @@ fn main() {
 
         // Start new game
 
-        if input == 1 {
-            continue;
-        } else {
-            start_player_game();
+        match input {
+            1 => start_computer_game(verbose, with_draw_rules(Game::new(), auto_threefold, no_fifty_move, no_repetition), threads),
+            2 => start_player_game(with_draw_rules(Game::new(), auto_threefold, no_fifty_move, no_repetition)),
+            3 => start_analysis(),
+            _ => {
+                if let Some(game) = start_position_editor() {
+                    start_player_game(with_draw_rules(game, auto_threefold, no_fifty_move, no_repetition));
+                }
+            }
         }
     }
 }
@@ -82,57 +145,728 @@ fn display_home_screen() {
     
     1. New game against computer opponent
     2. New two-player game
+    3. Analyze positions
+    4. Edit a position to play from
+
+    Enter 'quit' to exit
     ");
 }
 
+// Apply draw rule settings to game
+
+fn with_draw_rules(mut game: Game, auto_threefold: bool, no_fifty_move: bool, no_repetition: bool) -> Game {
+    game.automatic_threefold = auto_threefold;
+    game.fifty_move_rule = !no_fifty_move;
+    game.repetition_rule = !no_repetition;
+    game
+}
+
 // Start two player game
 
-fn start_player_game() {
+fn start_player_game(mut game: Game) {
+    // Report result without prompting for moves if position is already over
+
     util::clear_screen();
-    let mut game = Game::new();
+    let side = game.turn;
+    if display_game_result(&mut game, side) {
+        wait_for_enter();
+        return;
+    }
+
+    // Get optional time control
+
+    let clock = loop {
+        println!("Enter base minutes and increment seconds for a timed game (e.g. '5 3'), or press enter for an untimed game:\n");
+
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        util::clear_screen();
+        if input.is_empty() {
+            break None;
+        }
+        match Clock::parse(&input) {
+            Ok(clock) => break Some(clock),
+            Err(error) => println!("{}\n", error.bold())
+        }
+    };
+
+    game.clock = clock;
 
     loop {
         // Make player move
 
         println!("Player (black) vs. Player (white)\n");
         let mov = game.player_move();
+        if let Err(MoveError::EndOfInput) = mov {
+            exit_at_end_of_input();
+        }
         util::clear_screen();
+        if game.quit {
+            return;
+        }
         
         if let Err(error) = mov {
-            println!("{}\n", error.bold());
+            println!("{}\n", error.to_string().bold());
             continue;
         }
 
         // Check for game result
 
-        match game.get_game_result() {
-            GameResult::Win(side) => {
-                match side {
-                    White => println!("{}\n", "White wins by checkmate!".bold()),
-                    Black => println!("{}\n", "Black wins by checkmate!".bold())
-                }
-                game.display();
-                break;
-            },
-            GameResult::Draw(typ) => {
-                match typ {
-                    DrawType::Repetition => println!("{}\n", "Game is a draw by repetition".bold()),
-                    DrawType::Stalemate => println!("{}\n", "Game is a draw by stalemate".bold()),
-                    DrawType::Material => println!("{}\n", "Game is a draw by insufficient material".bold()),
-                    DrawType::FiftyMove => println!("{}\n", "Game is a draw by the fifty move rule".bold())
-                }
-                game.display();
-                break;
-            },
-            GameResult::None => ()
+        let side = game.turn;
+        if display_game_result(&mut game, side) {
+            break;
         }
     }
 
-    // Wait for enter to continue
+    wait_for_enter();
+}
+
+// Start game against computer opponent
 
+fn start_computer_game(verbose: bool, mut game: Game, threads: usize) {
+    // Report result without prompting for moves if position is already over
+
+    util::clear_screen();
+    let side = game.turn;
+    if display_game_result(&mut game, side) {
+        wait_for_enter();
+        return;
+    }
+
+    // Get engine difficulty
+
+    let difficulty = loop {
+        println!("Choose computer difficulty:\n");
+        println!("    1. Easy");
+        println!("    2. Medium");
+        println!("    3. Hard\n");
+
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        util::clear_screen();
+        match input.as_str() {
+            "1" => break Difficulty::Easy,
+            "2" => break Difficulty::Medium,
+            "3" => break Difficulty::Hard,
+            input => println!("{}\n", format!("'{input}' isn't a valid difficulty, please try again").bold())
+        }
+    };
+
+    // Get player side
+
+    let player = loop {
+        println!("Choose your side:\n");
+        println!("    1. White");
+        println!("    2. Black\n");
+
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        util::clear_screen();
+        match input.as_str() {
+            "1" => break White,
+            "2" => break Black,
+            input => println!("{}\n", format!("'{input}' isn't a valid side, please try again").bold())
+        }
+    };
+
+    let book = Book::new();
+    let mut rng = engine::create_rng(engine::get_seed());
+    let header = format!(
+        "Player ({}) vs. Computer ({})\n",
+        player.name().to_lowercase(),
+        player.get_opposite().name().to_lowercase()
+    );
+
+    play_computer_game(&mut game, player, |game| {
+        // Make player move
+
+        println!("{header}");
+        let mov = game.player_move();
+        if let Err(MoveError::EndOfInput) = mov {
+            exit_at_end_of_input();
+        }
+        util::clear_screen();
+        if game.quit {
+            return false;
+        }
+
+        if let Err(error) = mov {
+            println!("{}\n", error.to_string().bold());
+        }
+        true
+    }, |game| {
+        // Make computer move
+
+        let (san, stats) = match make_computer_move(game, &book, difficulty, threads, &mut rng) {
+            Some(played) => played,
+            None => return false
+        };
+        println!("{}\n", format!("Computer played {san}").bold());
+        if verbose {
+            println!(
+                "Searched {} nodes to depth {} in {} ms (score {})\n",
+                stats.nodes,
+                stats.depth,
+                stats.elapsed_ms,
+                stats.score
+            );
+        }
+        true
+    });
+
+    if !game.quit {
+        wait_for_enter();
+    }
+}
+
+// Alternate player and computer turns starting from side to move until game ends or a turn returns false
+// Computer moves first when player is black, and results are shown with board from player side
+
+fn play_computer_game(
+    game: &mut Game,
+    player: Side,
+    mut player_turn: impl FnMut(&mut Game) -> bool,
+    mut computer_turn: impl FnMut(&mut Game) -> bool
+) {
+    loop {
+        let playing = if game.turn == player { player_turn(game) } else { computer_turn(game) };
+        if !playing || display_game_result(game, player) {
+            break;
+        }
+    }
+}
+
+// Read trimmed line from terminal and exit cleanly at end of input
+
+fn read_input() -> Result<String, String> {
+    match util::read_input()? {
+        Some(input) => Ok(input),
+        None => exit_at_end_of_input()
+    }
+}
+
+// Exit program after input ends
+
+fn exit_at_end_of_input() -> ! {
+    println!();
+    process::exit(0);
+}
+
+// Choose and make computer move and get move in SAN with search statistics
+
+fn make_computer_move(
+    game: &mut Game,
+    book: &Book,
+    difficulty: Difficulty,
+    threads: usize,
+    rng: &mut ChaCha8Rng
+) -> Option<(String, SearchStats)> {
+    let (mov, stats) = engine::choose_move(game, book, difficulty, threads, rng);
+    let (mov, promotion) = mov?;
+    let san = move_input::to_san(game, mov, promotion);
+    game.make_move(mov, promotion).ok()?;
+    Some((san, stats))
+}
+
+// Wait for enter to continue
+
+fn wait_for_enter() {
     println!();
     println!("Press enter to continue:");
-    let mut input = String::new();
-    let _ = io::stdin().read_line(&mut input);
+    let _ = read_input();
+    util::clear_screen();
+}
+
+// Analyze positions and display best engine moves
+
+fn start_analysis() {
+    util::clear_screen();
+
+    loop {
+        // Get position from FEN or moves from start
+
+        println!("Enter a FEN position or moves from the start position (empty to return):\n");
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+        if input.is_empty() {
+            break;
+        }
+
+        util::clear_screen();
+        let mut game = match read_position(&input) {
+            Ok(game) => game,
+            Err(error) => {
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        // Display ranked engine moves
+
+        game.display();
+        println!();
+        let ranked = engine::rank_moves(&mut game, ANALYSIS_MOVES);
+        if ranked.is_empty() {
+            println!("{}\n", "No legal moves in position".bold());
+            continue;
+        }
+        for (i, (mov, score)) in ranked.into_iter().enumerate() {
+            let promotion = if game.is_promotion(mov) { Some(Queen) } else { None };
+            let san = move_input::to_san(&mut game, mov, promotion);
+            println!("    {}. {san} ({})", i + 1, engine::format_score(score));
+        }
+        println!();
+    }
+
+    util::clear_screen();
+}
+
+// Edit position from start position and get game to play from it
+// Every edit is checked so the position always has one king each and the side not to move isn't in check
+
+fn start_position_editor() -> Option<Game> {
     util::clear_screen();
+    let mut game = Game::new();
+
+    loop {
+        // Get editor command
+
+        game.display();
+        println!();
+        println!("Enter '+Pe4' to place a piece (lowercase for black), '-e4' to remove a piece, 'w' or 'b' to set the side to move,");
+        println!("'castle KQkq' or 'castle -' to set castle rights, 'clear' to remove all pieces except kings, 'fen <FEN>' to load a position,");
+        println!("or 'play' to start a two-player game (empty to return):\n");
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        util::clear_screen();
+        if input.is_empty() || util::is_quit(&input) {
+            return None;
+        }
+        if input == "play" {
+            return Some(game);
+        }
+        if let Err(error) = edit_position(&mut game, &input) {
+            println!("{}\n", error.bold());
+        }
+    }
+}
+
+// Apply position editor command to game
+
+fn edit_position(game: &mut Game, input: &str) -> Result<(), String> {
+    // Load position from FEN
+
+    if let Some(fen) = input.strip_prefix("fen ") {
+        *game = Game::from_fen(fen.trim())?;
+        return Ok(());
+    }
+
+    // Place or remove piece
+
+    if let Some(placement) = input.strip_prefix('+') {
+        let mut chars = placement.chars();
+        let letter = chars.next().unwrap_or(' ');
+        let side = if letter.is_ascii_uppercase() { White } else { Black };
+        let typ = PieceType::get_type(letter.to_ascii_uppercase())
+            .ok_or_else(|| format!("'{letter}' isn't a valid piece, please try again"))?;
+        let point: Point = chars.as_str().parse()?;
+        return game.set_piece(point, Piece { side, typ });
+    }
+    if let Some(square) = input.strip_prefix('-') {
+        return game.clear_piece(square.parse()?);
+    }
+
+    // Set side to move or castle rights
+
+    if let Some(side) = input.strip_prefix("castle ") {
+        let rights = side.trim();
+        if rights != "-" && !rights.chars().all(|char| "KQkq".contains(char)) {
+            return Err(format!("'{rights}' isn't a valid castling field, please try again"));
+        }
+        return game.set_castle_rights(CastleRights {
+            white: (rights.contains('Q'), rights.contains('K')),
+            black: (rights.contains('q'), rights.contains('k'))
+        });
+    }
+    let mut chars = input.chars();
+    if let (Some(side), None) = (chars.next().and_then(Side::from_char), chars.next()) {
+        return game.set_turn(side);
+    }
+
+    // Remove all pieces except kings
+
+    if input == "clear" {
+        let mut board = [[Square::Empty; SIZE]; SIZE];
+        for side in [White, Black] {
+            let king = game.king_positions.get_pos(side);
+            board[king.0][king.1] = game.board[king.0][king.1];
+        }
+        let no_rights = CastleRights { white: (false, false), black: (false, false) };
+        *game = Game::from_position(game.turn, board, no_rights, None, 0, 1)?;
+        return Ok(());
+    }
+    Err(format!("'{input}' isn't a valid editor command, please try again"))
+}
+
+// Read position from FEN or moves played from start position
+
+fn read_position(input: &str) -> Result<Game, String> {
+    if let Ok(game) = Game::from_fen(input) {
+        return Ok(game);
+    }
+
+    let mut game = Game::new();
+    for mov in input.split_whitespace() {
+        let move_data = move_input::parse_move(mov).map_err(|error| error.to_string())?;
+        game.apply_player_move(mov, move_data).map_err(|error| error.to_string())?;
+    }
+    Ok(game)
+}
+
+// Print game result and final board from side if game is over
+
+fn display_game_result(game: &mut Game, side: Side) -> bool {
+    match game.get_game_result() {
+        GameResult::Win(winner, typ) => {
+            let reason = match typ {
+                WinType::Checkmate => "by checkmate",
+                WinType::Time => "on time"
+            };
+            println!("{}\n", format!("{} wins {reason}!", winner.name()).bold());
+            game.display_from(side);
+            true
+        },
+        GameResult::Draw(typ) => {
+            match typ {
+                DrawType::Repetition => println!("{}\n", "Game is a draw by repetition".bold()),
+                DrawType::Stalemate => println!("{}\n", "Game is a draw by stalemate".bold()),
+                DrawType::Material => println!("{}\n", "Game is a draw by insufficient material".bold()),
+                DrawType::FiftyMove => println!("{}\n", "Game is a draw by the fifty move rule".bold()),
+                DrawType::Agreement => println!("{}\n", "Game is a draw by agreement".bold()),
+                DrawType::TimeMaterial => println!("{}\n", "Game is a draw, time ran out but the opponent has only a king".bold())
+            }
+            game.display_from(side);
+            true
+        },
+        GameResult::None => false
+    }
+}
+
+// Run perft to depth from optional FEN and print node counts for each move
+
+fn run_perft(args: &[String]) {
+    // Parse depth and position
+
+    let depth: u32 = match args.first().map(|depth| depth.parse()) {
+        Some(Ok(depth)) => depth,
+        _ => {
+            eprintln!("Usage: --perft <depth> [fen]");
+            return;
+        }
+    };
+    let game = if args.len() > 1 {
+        Game::from_fen(&args[1..].join(" "))
+    } else {
+        Ok(Game::new())
+    };
+    let mut game = match game {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+
+    // Count nodes after each legal move
+
+    let divide = game.perft_divide(depth);
+    for (mov, promotion, nodes) in &divide {
+        println!("{}: {nodes}", move_input::to_coordinates(*mov, *promotion));
+    }
+    println!();
+    println!("Nodes searched: {}", divide.iter().map(|(_, _, nodes)| nodes).sum::<u64>());
+}
+
+// Play tournament between default evaluation weights and material only weights and print each game and record
+
+fn run_tournament(args: &[String]) {
+    // Parse game count, depth, and seed
+
+    let numbers: Vec<Option<u64>> = args.iter().map(|arg| arg.parse().ok()).collect();
+    let (games, depth, seed) = match numbers[..] {
+        [Some(games), Some(depth)] => (games, depth, engine::DEFAULT_SEED),
+        [Some(games), Some(depth), Some(seed)] => (games, depth, seed),
+        _ => {
+            eprintln!("Usage: tournament <games> <depth> [seed]");
+            return;
+        }
+    };
+    let material_only = EvalParams {
+        king_attack_penalty: 0,
+        pawn_shelter_bonus: 0,
+        mobility_weights: [(Knight, 0), (Bishop, 0), (Rook, 0), (Queen, 0)],
+        doubled_pawn_penalty: 0,
+        isolated_pawn_penalty: 0,
+        passed_pawn_bonus: [0; SIZE],
+        ..engine::DEFAULT_EVAL_PARAMS
+    };
+
+    // Play games and print result of each game for default weights
+
+    let result = match engine::run_tournament(&engine::DEFAULT_EVAL_PARAMS, &material_only, games as u32, depth as u32, &[], seed) {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+    for (index, (side, game_result)) in result.games.iter().enumerate() {
+        let outcome = match game_result {
+            GameResult::Win(winner, _) if winner == side => "win",
+            GameResult::Win(_, _) => "loss",
+            _ => "draw"
+        };
+        println!("Game {} (A as {}): {outcome}", index + 1, side.name().to_lowercase());
+    }
+    println!("A vs B: {result}");
+}
+
+// Replay PGN from standard input and print final position FEN and result
+
+fn run_replay() {
+    let pgn = match io::read_to_string(io::stdin()) {
+        Ok(pgn) => pgn,
+        Err(error) => {
+            eprintln!("Error reading PGN: {error}");
+            process::exit(1);
+        }
+    };
+    let mut game = match Game::from_pgn(&pgn) {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+    };
+
+    println!("{}", game.to_fen());
+    println!("{}", get_pgn_result(&mut game, &pgn));
+}
+
+// Get result from final position or PGN result for resignations and unfinished games
+
+fn get_pgn_result<'a>(game: &mut Game, pgn: &'a str) -> &'a str {
+    match game.get_game_result() {
+        GameResult::None => pgn.split_whitespace().rev().find(|token| PGN_RESULTS.contains(token)).unwrap_or("*"),
+        result => result.to_pgn()
+    }
+}
+
+// Step through PGN game from file one move at a time with enter and back with 'b'
+
+fn run_viewer(args: &[String]) {
+    // Load game and get moves from starting position
+
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: view <pgn file>");
+            return;
+        }
+    };
+    let pgn = match fs::read_to_string(path) {
+        Ok(pgn) => pgn,
+        Err(error) => {
+            eprintln!("Error reading '{path}': {error}");
+            process::exit(1);
+        }
+    };
+    let mut game = match Game::from_pgn(&pgn) {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+    };
+    let result = get_pgn_result(&mut game, &pgn);
+    let (mut game, moves) = game.start_and_moves();
+
+    // Get SAN of each move by replaying game
+
+    let mut replay = game.clone();
+    let mut sans = vec![];
+    for &(mov, promotion) in &moves {
+        sans.push(move_input::to_san(&mut replay, mov, promotion));
+        if replay.make_move(mov, promotion).is_err() {
+            break;
+        }
+    }
+
+    util::clear_screen();
+    let mut ply = 0;
+    loop {
+        // Display last move played and board
+
+        match ply {
+            0 => println!("Start of game\n"),
+            _ => {
+                let (number, side) = game.get_move_number(ply - 1);
+                let dots = if side == White { "." } else { "..." };
+                println!("{}\n", format!("{number}{dots} {}", sans[ply - 1]).bold());
+            }
+        }
+        game.display_from(White);
+        println!();
+        if ply == sans.len() {
+            println!("End of game ({result})\n");
+        }
+        println!("Press enter for the next move, 'b' to go back, or 'quit' to exit (move {ply} of {}):\n", sans.len());
+
+        let input = match read_input() {
+            Ok(input) => input,
+            Err(error) => {
+                util::clear_screen();
+                println!("{}\n", error.bold());
+                continue;
+            }
+        };
+
+        // Step forward or back within game
+
+        util::clear_screen();
+        if util::is_quit(&input) {
+            break;
+        }
+        match input.as_str() {
+            "" if ply == sans.len() => println!("{}\n", "Already at the end of the game".bold()),
+            "" => {
+                let (mov, promotion) = moves[ply];
+                if game.make_move(mov, promotion).is_ok() {
+                    ply += 1;
+                }
+            },
+            "b" if ply == 0 => println!("{}\n", "Already at the start of the game".bold()),
+            "b" => {
+                game.undo();
+                ply -= 1;
+            },
+            input => println!("{}\n", format!("'{input}' isn't a valid viewer command, please try again").bold())
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+
+    // Computer game loop lets computer move first as white when player is black, with board shown from black
+
+    #[test]
+    fn player_as_black() {
+        let mut game = Game::new();
+        let book = Book::new();
+        let mut rng = engine::create_rng(engine::DEFAULT_SEED);
+        let player = Black;
+
+        let mut replies = ["e5", "Nc6", "Nf6"].into_iter();
+        play_computer_game(&mut game, player, |game| {
+            let Some(input) = replies.next() else {
+                return false;
+            };
+            assert_eq!((game.turn, game.history.len() % 2), (player, 1));
+            let move_data = move_input::parse_move(input).unwrap();
+            assert!(game.apply_player_move(input, move_data).is_ok(), "{input}");
+            true
+        }, |game| {
+            assert_eq!((game.turn, game.history.len() % 2), (White, 0));
+            make_computer_move(game, &book, Difficulty::Easy, 1, &mut rng).is_some()
+        });
+        assert_eq!(game.history.len(), 7);
+        assert_eq!(game.fullmove, 4);
+        for (ply, record) in game.history.iter().enumerate() {
+            let Square::Full(piece) = record.board[record.mov.from.0][record.mov.from.1] else {
+                panic!("no piece moved on ply {ply}");
+            };
+            assert_eq!(piece.side, if ply % 2 == 0 { White } else { Black });
+        }
+
+        // Black view has rank 1 at the top and the h-file on the left
+
+        let board = game.render_from(player, false);
+        let mut lines = board.lines();
+        assert_eq!(lines.next(), Some("  h g f e d c b a "));
+        assert!(lines.next().unwrap().starts_with("1 "));
+    }
+
+    // Positions loaded already over are reported as finished before any moves
+
+    #[test]
+    fn finished_positions() {
+        let positions = [
+            ("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", GameResult::Win(Black, WinType::Checkmate)),
+            ("8/8/8/4k3/8/8/2N5/4K3 w - - 0 1", GameResult::Draw(DrawType::Material)),
+            ("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", GameResult::Draw(DrawType::Stalemate))
+        ];
+        for (fen, result) in positions {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.get_game_result(), result, "{fen}");
+            let side = game.turn;
+            assert!(display_game_result(&mut game, side), "{fen}");
+        }
+        assert!(!display_game_result(&mut Game::new(), White));
+    }
+
+    // Losing engine only steers into a repeated position as a draw when the repetition rule is on
+
+    #[test]
+    fn search_follows_repetition_rule() {
+        for no_repetition in [false, true] {
+            let game = Game::from_fen("kq6/8/8/8/8/8/8/6NK w - - 0 1").unwrap();
+            let mut game = with_draw_rules(game, false, false, no_repetition);
+            for input in ["g1f3", "a8a7", "f3g1", "a7a8"] {
+                let (mov, promotion) = move_input::parse_coordinates(input).unwrap();
+                game.make_move(mov, promotion).unwrap();
+            }
+            let (_, stats) = engine::search(&mut game, &Book::empty(), 3, &mut engine::create_rng(engine::DEFAULT_SEED));
+            if no_repetition {
+                assert!(stats.score < -300, "{}", stats.score);
+            } else {
+                assert!(stats.score > -100, "{}", stats.score);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -1,18 +1,18 @@
 // Imports
 
-use crate::board::{ SIZE, PROMOTION_PIECES };
+use crate::board::{ SIZE, PROMOTION_PIECES, PROMOTION_ROWS };
 use crate::board::Side::{ White, Black };
 use crate::board::{ Square, Point };
-use crate::board::PieceType::{ self, Pawn, King };
-use crate::board::CastleDirection;
+use crate::board::PieceType::{ self, Pawn, Queen, King };
+use crate::board::{ Move, CastleDirection };
 use crate::board::Game;
 use crate::moves;
-
-use std::io;
+use crate::game::MoveError;
+use crate::util;
 
 // Parsed player move
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub struct PlayerMove {
     pub piece: PieceType,
@@ -21,7 +21,7 @@ pub struct PlayerMove {
     pub typ: MoveType
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub enum File {
     Row(usize),
@@ -29,41 +29,104 @@ pub enum File {
     Any
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub enum MoveType {
     Move,
     Capture,
     Promotion(PieceType),
     CapturePromotion(PieceType),
+    EnPassant,
     Castle(CastleDirection)
 }
 
-// Read and parse player move input from terminal
+// Read player move input from terminal
+
+pub fn get_player_input() -> Result<String, MoveError> {
+    util::read_input()
+        .map_err(MoveError::Other)?
+        .ok_or(MoveError::EndOfInput)
+}
 
-pub fn get_player_move() -> Result<(String, PlayerMove), String> {
-    // Read terminal input
+// Read promotion piece from terminal with queen as default
 
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_err() {
-        return Err(String::from("Error reading input, please try again"));
+pub fn get_promotion() -> Result<PieceType, MoveError> {
+    println!("Promote to (Q/R/B/N)?\n");
+    let input = get_player_input()?;
+    if input.is_empty() {
+        return Ok(Queen);
     }
-    let input = String::from(input.trim());
 
-    // Parse move
+    let mut chars = input.chars();
+    match (chars.next().and_then(|c| PieceType::get_type(c.to_ascii_uppercase())), chars.next()) {
+        (Some(promote), None) if PROMOTION_PIECES.contains(&promote) => Ok(promote),
+        _ => Err(MoveError::InvalidPromotionPiece(input))
+    }
+}
+
+// Parse move in coordinate notation like e2e4 or e7e8q
+// Only strings fully matching [a-h][1-8][a-h][1-8][qrbn]? are coordinates, and no such
+// string is valid SAN, so callers try coordinates first and fall back to parse_move
 
-    let move_data = parse_move(&input)?;
-    Ok((input, move_data))
+pub fn parse_coordinates(input: &str) -> Option<(Move, Option<PieceType>)> {
+    let chars: Vec<char> = input.chars().collect();
+    if chars.len() != 4 && chars.len() != 5 {
+        return None;
+    }
+
+    let get_point = |chars: &[char]| chars.iter().collect::<String>().parse::<Point>().ok();
+    let mov = Move {
+        from: get_point(&chars[0..2])?,
+        to: get_point(&chars[2..4])?
+    };
+
+    // Get optional promotion piece
+
+    let promotion = match chars.get(4) {
+        Some(c) if !c.is_ascii_lowercase() => return None,
+        Some(c) => match PieceType::get_type(c.to_ascii_uppercase()) {
+            Some(promote) if PROMOTION_PIECES.contains(&promote) => Some(promote),
+            _ => return None
+        },
+        None => None
+    };
+    Some((mov, promotion))
 }
 
 // Parse player input into move data
 
-pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
+pub fn parse_move(input: &str) -> Result<PlayerMove, MoveError> {
+    // Parse normalized input if casing or whitespace differs
+
+    let normalized = normalize_move(input);
+    if normalized != input {
+        return parse_move(&normalized);
+    }
+
+    // Ignore check and checkmate suffixes like Nf3+ or Qh7#
+
+    if let Some(mov) = input.strip_suffix(['+', '#']) {
+        return parse_move(mov);
+    }
+
+    // Parse pawn captures with en passant suffix like exd6 e.p.
+
+    if let Some(capture) = input.strip_suffix("e.p.") {
+        let move_data = parse_move(capture.trim_end())?;
+        if move_data.piece == Pawn && matches!(move_data.typ, MoveType::Capture) {
+            return Ok(PlayerMove {
+                typ: MoveType::EnPassant,
+                ..move_data
+            });
+        }
+        return Err(MoveError::InvalidEnPassant(String::from(input)));
+    }
+
     // Check input characters
 
     let chars: Vec<char> = input.chars().collect();
     if chars.len() < 2 {
-        return Err(format!("'{input}' isn't a valid move, please try again"));
+        return Err(MoveError::Invalid(String::from(input)));
     }
 
     // Check castle moves
@@ -119,7 +182,7 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
                     ),
                     typ: MoveType::Capture
                 });
-            } else if (chars[1] == char::from_digit(SIZE as u32, 10).unwrap() || chars[1] == '1') && chars[2] == '=' {
+            } else if (chars[1] == rows[PROMOTION_ROWS.0] || chars[1] == rows[PROMOTION_ROWS.1]) && chars[2] == '=' {
                 // Pawn promotion
 
                 if let Some(promote) = PieceType::get_type(chars[3]) {
@@ -135,11 +198,11 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
                             typ: MoveType::Promotion(promote)
                         });
                     }
-                    return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+                    return Err(MoveError::InvalidPromotion(String::from(input)));
                 }
-                return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+                return Err(MoveError::InvalidPromotion(String::from(input)));
             }
-            return Err(format!("'{input}' isn't a valid pawn capture, please try again"));
+            return Err(MoveError::InvalidPawnCapture(String::from(input)));
         } else if chars.len() == 6 && chars[1] == 'x' && cols.contains(&chars[2]) && rows.contains(&chars[3]) && chars[4] == '=' {
             // Pawn capture promotion
 
@@ -155,11 +218,11 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
                         typ: MoveType::CapturePromotion(promote)
                     });
                 }
-                return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+                return Err(MoveError::InvalidPromotion(String::from(input)));
             }
-            return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+            return Err(MoveError::InvalidPromotion(String::from(input)));
         }
-        return Err(format!("'{input}' isn't a valid pawn move, please try again"));
+        return Err(MoveError::InvalidPiece(String::from(input), Pawn));
     }
 
     // Check piece moves
@@ -208,7 +271,7 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
                     typ: MoveType::Move
                 });
             }
-            return Err(format!("'{input}' isn't a valid {} move, please try again", piece.get_name()));
+            return Err(MoveError::InvalidPiece(String::from(input), piece));
         } else if chars.len() == 5 && chars[2] == 'x' && cols.contains(&chars[3]) && rows.contains(&chars[4]) {
             // Disambiguating piece capture
 
@@ -217,7 +280,7 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
             } else if cols.contains(&chars[1]) {
                 File::Column(chars[1] as usize - char_start)
             } else {
-                return Err(format!("'{input}' isn't a valid {} move, please try again", piece.get_name()));
+                return Err(MoveError::InvalidPiece(String::from(input), piece));
             };
 
             return Ok(PlayerMove {
@@ -230,43 +293,78 @@ pub fn parse_move(input: &str) -> Result<PlayerMove, String> {
                 typ: MoveType::Capture
             });
         }
-        return Err(format!("'{input}' isn't a valid {} move, please try again", piece.get_name()));
+        return Err(MoveError::InvalidPiece(String::from(input), piece));
     }
 
     // Invalid piece specified
 
-    Err(format!("'{input}' isn't a valid move, please try again"))
+    Err(MoveError::Invalid(String::from(input)))
+}
+
+// Normalize move input by trimming whitespace, writing castle zeros or lowercase o as letter O,
+// and uppercasing lowercase piece and promotion letters
+// Lowercase b is kept as the pawn file, so bishop moves still need an uppercase B
+
+fn normalize_move(input: &str) -> String {
+    let input = input.trim();
+    let castle = input.trim_end_matches(['+', '#']);
+    if matches!(castle, "0-0" | "0-0-0" | "o-o" | "o-o-o") {
+        return input.replace(['0', 'o'], "O");
+    }
+
+    let mut chars: Vec<char> = input.chars().collect();
+    if let Some(first) = chars.first_mut() {
+        if matches!(first, 'n' | 'r' | 'q' | 'k') {
+            *first = first.to_ascii_uppercase();
+        }
+    }
+    if let Some(i) = chars.iter().position(|c| *c == '=') {
+        if let Some(promote) = chars.get_mut(i + 1) {
+            *promote = promote.to_ascii_uppercase();
+        }
+    }
+    chars.into_iter().collect()
 }
 
 // Validate move input with basic checks
 
-pub fn validate_move(input: &str, move_data: PlayerMove, game: &Game) -> Result<(), String> {
+pub fn validate_move(input: &str, move_data: PlayerMove, game: &Game) -> Result<(), MoveError> {
+    // Promotions must reach the last rank for the side to move
+
+    let last_row = match game.turn {
+        White => PROMOTION_ROWS.0,
+        Black => PROMOTION_ROWS.1
+    };
+
     match move_data.typ {
         MoveType::Move => {
             if game.board[move_data.to.0][move_data.to.1] != Square::Empty {
-                return Err(format!("'{input}' isn't a valid move, please try again"));
+                return Err(MoveError::Invalid(String::from(input)));
             }
-            if move_data.piece == Pawn && (move_data.to.0 == 0 || move_data.to.0 == SIZE - 1) {
-                return Err(format!("'{input}' must be a pawn promotion, please try again"));
+            if move_data.piece == Pawn && (move_data.to.0 == PROMOTION_ROWS.0 || move_data.to.0 == PROMOTION_ROWS.1) {
+                return Err(MoveError::MissingPromotion(String::from(input)));
             }
         },
         MoveType::Capture => if game.board[move_data.to.0][move_data.to.1] == Square::Empty {
-            return Err(format!("'{input}' isn't a valid capture, please try again"));
+            return Err(MoveError::InvalidCapture(String::from(input)));
+        },
+        MoveType::EnPassant => if move_data.piece != Pawn || !is_en_passant_target(move_data.to, game) {
+            return Err(MoveError::InvalidEnPassant(String::from(input)));
         },
-        MoveType::Promotion(_) => if (game.turn == White && move_data.to.0 < SIZE - 1) || (game.turn == Black && move_data.to.0 > 0) {
-            return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+        MoveType::Promotion(_) => if move_data.to.0 != last_row {
+            return Err(MoveError::WrongPromotionRank(String::from(input), game.turn));
         },
         MoveType::CapturePromotion(_) => {
             if game.board[move_data.to.0][move_data.to.1] == Square::Empty {
-                return Err(format!("'{input}' isn't a valid capture, please try again"));
+                return Err(MoveError::InvalidCapture(String::from(input)));
             }
-            if (game.turn == White && move_data.to.0 < SIZE - 1) || (game.turn == Black && move_data.to.0 > 0) {
-                return Err(format!("'{input}' isn't a valid pawn promotion, please try again"));
+            if move_data.to.0 != last_row {
+                return Err(MoveError::WrongPromotionRank(String::from(input), game.turn));
             }
         },
         MoveType::Castle(dir) => {
             if !game.castle_rights.has_right(game.turn, dir) {
-                return Err(format!("'{input}' isn't a valid castle move, please try again"));
+                return Err(MoveError::InvalidCastle(String::from(input)));
             }
             if !moves::can_castle(
                 game.turn,
@@ -274,10 +372,217 @@ pub fn validate_move(input: &str, move_data: PlayerMove, game: &Game) -> Result<
                 &game.board,
                 game.king_positions.get_pos(game.turn)
             ) {
-                return Err(format!("'{input}' castles through pieces or check, please try again"));
+                return Err(MoveError::CastleBlocked(String::from(input)));
             }
         }
     };
 
     Ok(())
+}
+
+// Get pawn capture onto en passant target as explicit en passant move
+
+pub fn detect_en_passant(move_data: PlayerMove, game: &Game) -> PlayerMove {
+    let capture = move_data.piece == Pawn && matches!(move_data.typ, MoveType::Capture);
+    if capture && game.board[move_data.to.0][move_data.to.1] == Square::Empty && is_en_passant_target(move_data.to, game) {
+        return PlayerMove {
+            typ: MoveType::EnPassant,
+            ..move_data
+        };
+    }
+    move_data
+}
+
+// Check if square is en passant target behind last double moved pawn
+
+fn is_en_passant_target(to: Point, game: &Game) -> bool {
+    match game.pawn_double_moved {
+        Some(point) => match game.turn {
+            White => to == Point(point.0 + 1, point.1),
+            Black => point.0 > 0 && to == Point(point.0 - 1, point.1)
+        },
+        None => false
+    }
+}
+
+// Get minimal standard algebraic notation for legal move
+
+pub fn to_san(game: &mut Game, mov: Move, promotion: Option<PieceType>) -> String {
+    let piece = match game.board[mov.from.0][mov.from.1] {
+        Square::Full(piece) => piece,
+        Square::Empty => return String::new()
+    };
+    let mut san = String::new();
+
+    if piece.typ == King && mov.from.1.abs_diff(mov.to.1) == 2 {
+        // Castle move
+
+        san.push_str(if mov.to.1 < mov.from.1 { "O-O-O" } else { "O-O" });
+    } else {
+        // Piece and disambiguating file or rank
+
+        let capture = game.board[mov.to.0][mov.to.1] != Square::Empty ||
+                      (piece.typ == Pawn && mov.from.1 != mov.to.1);
+        if piece.typ == Pawn {
+            if capture {
+                san.push(get_file_char(mov.from.1));
+            }
+        } else {
+            san.push(piece.typ.get_letter());
+
+            let others: Vec<Move> = game.legal_moves()
+                .into_iter()
+                .map(|(other, _)| other)
+                .filter(|other| {
+                    other.to == mov.to && other.from != mov.from &&
+                    game.board[other.from.0][other.from.1] == Square::Full(piece)
+                })
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|other| other.from.1 != mov.from.1) {
+                    san.push(get_file_char(mov.from.1));
+                } else if others.iter().all(|other| other.from.0 != mov.from.0) {
+                    san.push(get_rank_char(mov.from.0));
+                } else {
+                    san.push_str(&mov.from.to_string());
+                }
+            }
+        }
+
+        // Capture, destination square, and promotion
+
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&mov.to.to_string());
+        if let Some(promote) = promotion {
+            san.push('=');
+            san.push(promote.get_letter());
+        }
+    }
+
+    // Check or checkmate suffix
+
+    if game.make_move(mov, promotion).is_ok() {
+        if game.is_checkmate() {
+            san.push('#');
+        } else if game.in_check() {
+            san.push('+');
+        }
+        game.undo();
+    }
+
+    san
+}
+
+// Get coordinate notation for move like e2e4 or e7e8q
+
+pub fn to_coordinates(mov: Move, promotion: Option<PieceType>) -> String {
+    let mut coordinates = mov.to_string();
+    if let Some(promote) = promotion {
+        coordinates.push(promote.get_letter().to_ascii_lowercase());
+    }
+    coordinates
+}
+
+// Get file letter for column
+
+fn get_file_char(col: usize) -> char {
+    (b'a' + col as u8) as char
+}
+
+// Get rank digit for row
+
+fn get_rank_char(row: usize) -> char {
+    char::from_digit(row as u32 + 1, 10).unwrap()
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+    use crate::board::PieceType::{ Knight, Bishop, Rook };
+
+    fn player_move(piece: PieceType, from: File, to: &str, typ: MoveType) -> PlayerMove {
+        PlayerMove { piece, from, to: to.parse().unwrap(), typ }
+    }
+
+    fn error(input: &str) -> String {
+        parse_move(input).unwrap_err().to_string()
+    }
+
+    // Pawn moves, captures, promotions, and capture promotions
+
+    #[test]
+    fn parse_pawn_moves() {
+        assert_eq!(parse_move("e4"), Ok(player_move(Pawn, File::Column(4), "e4", MoveType::Move)));
+        assert_eq!(parse_move("exd5"), Ok(player_move(Pawn, File::Column(4), "d5", MoveType::Capture)));
+        assert_eq!(parse_move("e8=Q"), Ok(player_move(Pawn, File::Column(4), "e8", MoveType::Promotion(Queen))));
+        assert_eq!(parse_move("h1=N"), Ok(player_move(Pawn, File::Column(7), "h1", MoveType::Promotion(Knight))));
+        assert_eq!(parse_move("dxe8=B"), Ok(player_move(Pawn, File::Column(3), "e8", MoveType::CapturePromotion(Bishop))));
+        assert_eq!(parse_move("exd6 e.p."), Ok(player_move(Pawn, File::Column(4), "d6", MoveType::EnPassant)));
+    }
+
+    // Piece moves, captures, and disambiguation by file or rank
+
+    #[test]
+    fn parse_piece_moves() {
+        assert_eq!(parse_move("Nf3"), Ok(player_move(Knight, File::Any, "f3", MoveType::Move)));
+        assert_eq!(parse_move("Rxa8"), Ok(player_move(Rook, File::Any, "a8", MoveType::Capture)));
+        assert_eq!(parse_move("Nbd2"), Ok(player_move(Knight, File::Column(1), "d2", MoveType::Move)));
+        assert_eq!(parse_move("N1f3"), Ok(player_move(Knight, File::Row(0), "f3", MoveType::Move)));
+        assert_eq!(parse_move("Nbxd2"), Ok(player_move(Knight, File::Column(1), "d2", MoveType::Capture)));
+        assert_eq!(parse_move("R1xa3"), Ok(player_move(Rook, File::Row(0), "a3", MoveType::Capture)));
+    }
+
+    // Castling in both directions with letter O or zero
+
+    #[test]
+    fn parse_castles() {
+        let castle = |dir| PlayerMove { piece: King, from: File::Any, to: Point(0, 0), typ: MoveType::Castle(dir) };
+        assert_eq!(parse_move("O-O"), Ok(castle(CastleDirection::H)));
+        assert_eq!(parse_move("O-O-O"), Ok(castle(CastleDirection::A)));
+        assert_eq!(parse_move("0-0"), Ok(castle(CastleDirection::H)));
+    }
+
+    // Check and checkmate suffixes are ignored
+
+    #[test]
+    fn parse_check_suffixes() {
+        assert_eq!(parse_move("e4+"), parse_move("e4"));
+        assert_eq!(parse_move("Nf3+"), parse_move("Nf3"));
+        assert_eq!(parse_move("Qxf7#"), parse_move("Qxf7"));
+        assert_eq!(parse_move("O-O#"), parse_move("O-O"));
+    }
+
+    // Malformed input gives the error message for the piece or move type
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(error("e9"), "'e9' isn't a valid pawn move, please try again");
+        assert_eq!(error("Kx"), "'Kx' isn't a valid king move, please try again");
+        assert_eq!(error("O-O-O-O"), "'O-O-O-O' isn't a valid move, please try again");
+        assert_eq!(error("e8=K"), "'e8=K' isn't a valid pawn promotion, please try again");
+        assert_eq!(error("e8=P"), "'e8=P' isn't a valid pawn promotion, please try again");
+        assert_eq!(error("dxe8=K"), "'dxe8=K' isn't a valid pawn promotion, please try again");
+        assert_eq!(error(""), "'' isn't a valid move, please try again");
+        assert_eq!(error("exd"), "'exd' isn't a valid pawn move, please try again");
+        assert_eq!(error("Nx3"), "'Nx3' isn't a valid knight move, please try again");
+        assert_eq!(error("Qxx4"), "'Qxx4' isn't a valid queen move, please try again");
+        assert_eq!(error("Zf3"), "'Zf3' isn't a valid move, please try again");
+    }
+
+    // Coordinate input is never parsed as algebraic input and the reverse
+
+    #[test]
+    fn coordinate_and_algebraic() {
+        for input in ["b1c3", "e2e4", "e7e8q", "b7b8b", "a1a2", "b2b1n"] {
+            assert!(parse_coordinates(input).is_some(), "{input}");
+            assert!(parse_move(input).is_err(), "{input}");
+        }
+        for input in ["bxc3", "Bc3", "b1", "e8=Q", "B1c3", "e7e8Q", "b7b8B"] {
+            assert!(parse_coordinates(input).is_none(), "{input}");
+        }
+        assert!(parse_move("Bc3").is_ok());
+        assert!(parse_move("bxc3").is_ok());
+    }
 }
\ No newline at end of file
@@ -1,9 +1,9 @@
 // Imports
 
-use crate::board::{ SIZE, CASTLE_COLUMNS };
+use crate::board::{ SIZE, PAWN_START_ROWS, INITIAL_POSITIONS, CASTLE_COLUMNS, CASTLE_EMPTY_COLUMNS };
 use crate::board::Side::{ self, White, Black };
 use crate::board::{ Square, Point };
-use crate::board::PieceType::{ Pawn, Knight, Bishop, Rook, Queen };
+use crate::board::PieceType::{ Pawn, Knight, Bishop, Rook, Queen, King };
 use crate::board::Move;
 use crate::board::CastleDirection;
 
@@ -14,9 +14,14 @@ const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
 const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
 const KING_MOVES: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, 1), (1, -1), (-1, -1)];
 
+// Check if row and column are on board
+
+pub fn on_board(row: i32, col: i32) -> bool {
+    row >= 0 && row < SIZE as i32 && col >= 0 && col < SIZE as i32
+}
+
 // Get pseudolegal pawn moves for pawn on board
 
-#[allow(clippy::collapsible_else_if)]
 pub fn get_pawn_moves(
     side: Side,
     row: usize,
@@ -24,37 +29,52 @@ pub fn get_pawn_moves(
     board: &[[Square; SIZE]; SIZE],
     pawn_double_moved: Option<Point>
 ) -> Vec<Point> {
+    let mut moves = vec![];
+    push_pawn_moves(side, row, col, board, pawn_double_moved, &mut moves);
+    moves
+}
+
+// Push pseudolegal pawn moves for pawn on board into buffer
+
+#[allow(clippy::collapsible_else_if)]
+pub fn push_pawn_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    pawn_double_moved: Option<Point>,
+    moves: &mut Vec<Point>
+) {
     // Check forward moves
 
-    let mut moves = vec![];
     if side == White {
         if board[row + 1][col] == Square::Empty {
             moves.push(Point(row + 1, col));
-            if row == 1 && board[row + 2][col] == Square::Empty {
+            if row == PAWN_START_ROWS.0 && board[row + 2][col] == Square::Empty {
                 moves.push(Point(row + 2, col));
             }
         }
     } else {
         if board[row - 1][col] == Square::Empty {
             moves.push(Point(row - 1, col));
-            if row == SIZE - 2 && board[row - 2][col] == Square::Empty {
+            if row == PAWN_START_ROWS.1 && board[row - 2][col] == Square::Empty {
                 moves.push(Point(row - 2, col));
             }
         }
     }
 
-    // Check diagonal captures
+    // Check diagonal captures, never targeting the king since it can't be captured
 
     if col > 0 {
         if side == White {
             if let Square::Full(piece) = &board[row + 1][col - 1] {
-                if piece.side == Black {
+                if piece.side == Black && piece.typ != King {
                     moves.push(Point(row + 1, col - 1));
                 }
             }
         } else {
             if let Square::Full(piece) = &board[row - 1][col - 1] {
-                if piece.side == White {
+                if piece.side == White && piece.typ != King {
                     moves.push(Point(row - 1, col - 1));
                 }
             }
@@ -63,13 +83,13 @@ pub fn get_pawn_moves(
     if col < SIZE - 1 {
         if side == White {
             if let Square::Full(piece) = &board[row + 1][col + 1] {
-                if piece.side == Black {
+                if piece.side == Black && piece.typ != King {
                     moves.push(Point(row + 1, col + 1));
                 }
             }
         } else {
             if let Square::Full(piece) = &board[row - 1][col + 1] {
-                if piece.side == White {
+                if piece.side == White && piece.typ != King {
                     moves.push(Point(row - 1, col + 1));
                 }
             }
@@ -79,24 +99,31 @@ pub fn get_pawn_moves(
     // Check en passant moves
 
     if let Some(point) = pawn_double_moved {
-        if row == point.0 {
-            if col == point.1 + 1 {
-                if side == White && board[row + 1][col - 1] == Square::Empty {
-                    moves.push(Point(row + 1, col - 1));
-                } else if board[row - 1][col - 1] == Square::Empty {
-                    moves.push(Point(row - 1, col - 1));
-                }
-            } else if col + 1 == point.1 {
-                if side == White && board[row + 1][col + 1] == Square::Empty {
-                    moves.push(Point(row + 1, col + 1));
-                } else if board[row + 1][col - 1] == Square::Empty {
-                    moves.push(Point(row + 1, col - 1));
+        if row == point.0 && (col == point.1 + 1 || col + 1 == point.1) {
+            if let Square::Full(piece) = board[point.0][point.1] {
+                let to_row = if side == White { row + 1 } else { row - 1 };
+                if piece.side != side && piece.typ == Pawn && board[to_row][point.1] == Square::Empty {
+                    moves.push(Point(to_row, point.1));
                 }
             }
         }
     }
+}
 
-    moves
+// Push squares attacked diagonally by pawn into buffer
+
+pub fn push_pawn_attacks(side: Side, row: usize, col: usize, moves: &mut Vec<Point>) {
+    let to_row = match side {
+        White if row < SIZE - 1 => row + 1,
+        Black if row > 0 => row - 1,
+        _ => return
+    };
+    if col > 0 {
+        moves.push(Point(to_row, col - 1));
+    }
+    if col < SIZE - 1 {
+        moves.push(Point(to_row, col + 1));
+    }
 }
 
 // Get pseudolegal knight moves for knight on board
@@ -108,14 +135,25 @@ pub fn get_knight_moves(
     board: &[[Square; SIZE]; SIZE]
 ) -> Vec<Point> {
     let mut moves = vec![];
-    let size = SIZE as i32;
+    push_knight_moves(side, row, col, board, &mut moves);
+    moves
+}
+
+// Push pseudolegal knight moves for knight on board into buffer
 
+pub fn push_knight_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    moves: &mut Vec<Point>
+) {
     for dir in KNIGHT_MOVES {
         let square = (row as i32 + dir.0, col as i32 + dir.1);
-        if square.0 >= 0 && square.0 < size && square.1 >= 0 && square.1 < size {
+        if on_board(square.0, square.1) {
             let mov = Point(square.0 as usize, square.1 as usize);
             if let Square::Full(piece) = board[mov.0][mov.1] {
-                if piece.side != side {
+                if piece.side != side && piece.typ != King {
                     moves.push(mov);
                 }
                 continue;
@@ -123,8 +161,6 @@ pub fn get_knight_moves(
             moves.push(mov);
         }
     }
-
-    moves
 }
 
 // Get pseudolegal bishop moves for bishop on board
@@ -136,14 +172,25 @@ pub fn get_bishop_moves(
     board: &[[Square; SIZE]; SIZE]
 ) -> Vec<Point> {
     let mut moves = vec![];
-    let size = SIZE as i32;
+    push_bishop_moves(side, row, col, board, &mut moves);
+    moves
+}
+
+// Push pseudolegal bishop moves for bishop on board into buffer
 
+pub fn push_bishop_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    moves: &mut Vec<Point>
+) {
     for dir in BISHOP_DIRECTIONS {
         let mut square = (row as i32 + dir.0, col as i32 + dir.1);
-        while square.0 >= 0 && square.0 < size && square.1 >= 0 && square.1 < size {
+        while on_board(square.0, square.1) {
             let mov = Point(square.0 as usize, square.1 as usize);
             if let Square::Full(piece) = board[mov.0][mov.1] {
-                if piece.side != side {
+                if piece.side != side && piece.typ != King {
                     moves.push(mov);
                 }
                 break;
@@ -153,8 +200,6 @@ pub fn get_bishop_moves(
             square.1 += dir.1;
         }
     }
-
-    moves
 }
 
 // Get pseudolegal rook moves for rook on board
@@ -166,14 +211,25 @@ pub fn get_rook_moves(
     board: &[[Square; SIZE]; SIZE]
 ) -> Vec<Point> {
     let mut moves = vec![];
-    let size = SIZE as i32;
+    push_rook_moves(side, row, col, board, &mut moves);
+    moves
+}
+
+// Push pseudolegal rook moves for rook on board into buffer
 
+pub fn push_rook_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    moves: &mut Vec<Point>
+) {
     for dir in ROOK_DIRECTIONS {
         let mut square = (row as i32 + dir.0, col as i32 + dir.1);
-        while square.0 >= 0 && square.0 < size && square.1 >= 0 && square.1 < size {
+        while on_board(square.0, square.1) {
             let mov = Point(square.0 as usize, square.1 as usize);
             if let Square::Full(piece) = board[mov.0][mov.1] {
-                if piece.side != side {
+                if piece.side != side && piece.typ != King {
                     moves.push(mov);
                 }
                 break;
@@ -183,8 +239,6 @@ pub fn get_rook_moves(
             square.1 += dir.1;
         }
     }
-
-    moves
 }
 
 // Get pseudolegal queen moves for queen on board
@@ -196,14 +250,25 @@ pub fn get_queen_moves(
     board: &[[Square; SIZE]; SIZE]
 ) -> Vec<Point> {
     let mut moves = vec![];
-    let size = SIZE as i32;
+    push_queen_moves(side, row, col, board, &mut moves);
+    moves
+}
+
+// Push pseudolegal queen moves for queen on board into buffer
 
+pub fn push_queen_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    moves: &mut Vec<Point>
+) {
     for dir in BISHOP_DIRECTIONS.iter().chain(ROOK_DIRECTIONS.iter()) {
         let mut square = (row as i32 + dir.0, col as i32 + dir.1);
-        while square.0 >= 0 && square.0 < size && square.1 >= 0 && square.1 < size {
+        while on_board(square.0, square.1) {
             let mov = Point(square.0 as usize, square.1 as usize);
             if let Square::Full(piece) = board[mov.0][mov.1] {
-                if piece.side != side {
+                if piece.side != side && piece.typ != King {
                     moves.push(mov);
                 }
                 break;
@@ -213,8 +278,6 @@ pub fn get_queen_moves(
             square.1 += dir.1;
         }
     }
-
-    moves
 }
 
 // Get pseudolegal king moves for king on board
@@ -226,14 +289,25 @@ pub fn get_king_moves(
     board: &[[Square; SIZE]; SIZE]
 ) -> Vec<Point> {
     let mut moves = vec![];
-    let size = SIZE as i32;
+    push_king_moves(side, row, col, board, &mut moves);
+    moves
+}
+
+// Push pseudolegal king moves for king on board into buffer
 
+pub fn push_king_moves(
+    side: Side,
+    row: usize,
+    col: usize,
+    board: &[[Square; SIZE]; SIZE],
+    moves: &mut Vec<Point>
+) {
     for dir in KING_MOVES {
         let square = (row as i32 + dir.0, col as i32 + dir.1);
-        if square.0 >= 0 && square.0 < size && square.1 >= 0 && square.1 < size {
+        if on_board(square.0, square.1) {
             let mov = Point(square.0 as usize, square.1 as usize);
             if let Square::Full(piece) = board[mov.0][mov.1] {
-                if piece.side != side {
+                if piece.side != side && piece.typ != King {
                     moves.push(mov);
                 }
                 continue;
@@ -241,8 +315,20 @@ pub fn get_king_moves(
             moves.push(mov);
         }
     }
+}
 
-    moves
+// Check if move is a pawn of side capturing onto en passant target square behind last double moved pawn
+
+pub fn is_en_passant(side: Side, mov: Move, board: &[[Square; SIZE]; SIZE], pawn_double_moved: Option<Point>) -> bool {
+    let Some(pawn) = pawn_double_moved else {
+        return false;
+    };
+    let target = match side {
+        White => pawn.0 + 1,
+        Black => pawn.0.wrapping_sub(1)
+    };
+    let beside = mov.from.0 == pawn.0 && mov.from.1.abs_diff(pawn.1) == 1;
+    board[mov.from.0][mov.from.1] == Square::full(side, Pawn) && beside && mov.to == Point(target, pawn.1)
 }
 
 // Filter legal moves from possible moves
@@ -251,28 +337,137 @@ pub fn filter_legal_moves(
     side: Side,
     possible: Vec<Move>,
     board: &mut [[Square; SIZE]; SIZE],
+    pawn_double_moved: Option<Point>,
     king_pos: Point
 ) -> impl Iterator<Item=Move> + '_ {
-    possible.into_iter().filter(move |mov| {
-        // Make move on board
+    possible.into_iter().filter(move |mov| keeps_king_safe(side, *mov, board, pawn_double_moved, king_pos))
+}
+
+// Check if move doesn't leave king in check
+
+pub fn keeps_king_safe(
+    side: Side,
+    mov: Move,
+    board: &mut [[Square; SIZE]; SIZE],
+    pawn_double_moved: Option<Point>,
+    king_pos: Point
+) -> bool {
+    // Remove pawn captured en passant on different square than destination
+    // Both pawns leave their squares, so this catches diagonal pins on the capturing
+    // pawn and rank checks revealed by removing both pawns from the same rank
+
+    let replaced = board[mov.to.0][mov.to.1];
+    let en_passant = is_en_passant(side, mov, board, pawn_double_moved);
+    let captured = board[mov.from.0][mov.to.1];
+    if en_passant {
+        board[mov.from.0][mov.to.1] = Square::Empty;
+    }
+
+    // Make move on board
+
+    board[mov.to.0][mov.to.1] = board[mov.from.0][mov.from.1];
+    board[mov.from.0][mov.from.1] = Square::Empty;
+
+    let legal = !in_check(
+        side,
+        if mov.from == king_pos { mov.to } else { king_pos },
+        board
+    );
+
+    // Unmake move on board
+
+    board[mov.from.0][mov.from.1] = board[mov.to.0][mov.to.1];
+    board[mov.to.0][mov.to.1] = replaced;
+    if en_passant {
+        board[mov.from.0][mov.to.1] = captured;
+    }
+
+    legal
+}
 
-        let replaced = board[mov.to.0][mov.to.1];
-        board[mov.to.0][mov.to.1] = board[mov.from.0][mov.from.1];
-        board[mov.from.0][mov.from.1] = Square::Empty;
+// Get pieces pinned against king at position by enemy sliding pieces
 
-        let legal = !in_check(
-            side,
-            if mov.from == king_pos { mov.to } else { king_pos },
-            board
-        );
+pub fn get_pinned(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> [[bool; SIZE]; SIZE] {
+    let mut pinned = [[false; SIZE]; SIZE];
 
-        // Unmake move on board
+    for (dir, diagonal) in ROOK_DIRECTIONS.iter().map(|dir| (dir, false))
+                                          .chain(BISHOP_DIRECTIONS.iter().map(|dir| (dir, true))) {
+        // Find first own piece and next piece along direction
 
-        board[mov.from.0][mov.from.1] = board[mov.to.0][mov.to.1];
-        board[mov.to.0][mov.to.1] = replaced;
+        let mut blocker = None;
+        let mut square = (pos.0 as i32 + dir.0, pos.1 as i32 + dir.1);
+        while on_board(square.0, square.1) {
+            if let Square::Full(piece) = board[square.0 as usize][square.1 as usize] {
+                match blocker {
+                    None if piece.side == side => blocker = Some(square),
+                    None => break,
+                    Some(point) => {
+                        // Check for enemy sliding piece behind own piece
+
+                        let slides = if diagonal {
+                            piece.typ == Bishop || piece.typ == Queen
+                        } else {
+                            piece.typ == Rook || piece.typ == Queen
+                        };
+                        if piece.side != side && slides {
+                            pinned[point.0 as usize][point.1 as usize] = true;
+                        }
+                        break;
+                    }
+                }
+            }
+            square.0 += dir.0;
+            square.1 += dir.1;
+        }
+    }
 
-        legal
-    })
+    pinned
+}
+
+// Get squares attacked by side including squares defended by own pieces
+
+pub fn get_attacked_squares(side: Side, board: &[[Square; SIZE]; SIZE]) -> [[bool; SIZE]; SIZE] {
+    let mut attacked = [[false; SIZE]; SIZE];
+    let mut squares = vec![];
+
+    for row in 0..SIZE {
+        for col in 0..SIZE {
+            let piece = match board[row][col] {
+                Square::Full(piece) if piece.side == side => piece,
+                _ => continue
+            };
+
+            // Get attacks for piece with sliding pieces stopping at first blocker
+
+            let (dirs, slides): (&[(i32, i32)], bool) = match piece.typ {
+                Pawn => {
+                    push_pawn_attacks(side, row, col, &mut squares);
+                    (&[], false)
+                },
+                Knight => (&KNIGHT_MOVES, false),
+                Bishop => (&BISHOP_DIRECTIONS, true),
+                Rook => (&ROOK_DIRECTIONS, true),
+                Queen | King => (&KING_MOVES, piece.typ == Queen)
+            };
+            for dir in dirs {
+                let mut square = (row as i32 + dir.0, col as i32 + dir.1);
+                while on_board(square.0, square.1) {
+                    squares.push(Point(square.0 as usize, square.1 as usize));
+                    if !slides || board[square.0 as usize][square.1 as usize] != Square::Empty {
+                        break;
+                    }
+                    square.0 += dir.0;
+                    square.1 += dir.1;
+                }
+            }
+
+            for square in squares.drain(..) {
+                attacked[square.0][square.1] = true;
+            }
+        }
+    }
+
+    attacked
 }
 
 // Check if king at position is in check
@@ -280,10 +475,9 @@ pub fn filter_legal_moves(
 pub fn in_check(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> bool {
     // Check rook and queen horizontal attacks
 
-    let size = SIZE as i32;
     for dir in ROOK_DIRECTIONS {
         let mut square = (pos.0 as i32 + dir.0, pos.1 as i32 + dir.1);
-        while square.0 >= 0 && square.0 < size && square.1 > 0 && square.1 < size {
+        while on_board(square.0, square.1) {
             if let Square::Full(piece) = board[square.0 as usize][square.1 as usize] {
                 if (piece.typ == Rook || piece.typ == Queen) && piece.side != side {
                     return true;
@@ -299,7 +493,7 @@ pub fn in_check(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> bool
 
     for dir in BISHOP_DIRECTIONS {
         let mut square = (pos.0 as i32 + dir.0, pos.1 as i32 + dir.1);
-        while square.0 >= 0 && square.0 < size && square.1 > 0 && square.1 < size {
+        while on_board(square.0, square.1) {
             if let Square::Full(piece) = board[square.0 as usize][square.1 as usize] {
                 if (piece.typ == Bishop || piece.typ == Queen) && piece.side != side {
                     return true;
@@ -316,7 +510,7 @@ pub fn in_check(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> bool
     for mov in KNIGHT_MOVES {
         let row = pos.0 as i32 + mov.0;
         let col = pos.1 as i32 + mov.1;
-        if row >= 0 && row < size && col >= 0 && col < size {
+        if on_board(row, col) {
             if let Square::Full(piece) = board[row as usize][col as usize] {
                 if piece.typ == Knight && piece.side != side {
                     return true;
@@ -325,20 +519,36 @@ pub fn in_check(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> bool
         }
     }
 
-    // Check pawn attacks
+    // Check enemy king attacks on adjacent squares
 
-    if side == White && pos.0 < SIZE - 1 {
-        if pos.1 > 0 {
-            if let Square::Full(piece) = board[pos.0 + 1][pos.1 - 1] {
-                if piece.typ == Pawn && piece.side == Black {
+    for mov in KING_MOVES {
+        let row = pos.0 as i32 + mov.0;
+        let col = pos.1 as i32 + mov.1;
+        if on_board(row, col) {
+            if let Square::Full(piece) = board[row as usize][col as usize] {
+                if piece.typ == King && piece.side != side {
                     return true;
                 }
             }
         }
-        if pos.1 < SIZE - 1 {
-            if let Square::Full(piece) = board[pos.0 + 1][pos.1 + 1] {
-                if piece.typ == Pawn && piece.side == Black {
-                    return true;
+    }
+
+    // Check pawn attacks
+
+    if side == White {
+        if pos.0 < SIZE - 1 {
+            if pos.1 > 0 {
+                if let Square::Full(piece) = board[pos.0 + 1][pos.1 - 1] {
+                    if piece.typ == Pawn && piece.side == Black {
+                        return true;
+                    }
+                }
+            }
+            if pos.1 < SIZE - 1 {
+                if let Square::Full(piece) = board[pos.0 + 1][pos.1 + 1] {
+                    if piece.typ == Pawn && piece.side == Black {
+                        return true;
+                    }
                 }
             }
         }
@@ -364,6 +574,48 @@ pub fn in_check(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> bool
     false
 }
 
+// Get positions of pieces of side attacking square
+
+pub fn get_attackers(side: Side, pos: Point, board: &[[Square; SIZE]; SIZE]) -> Vec<Point> {
+    let mut attackers = vec![];
+
+    // Find sliding attackers as first piece along each ray
+
+    for (directions, typ) in [(ROOK_DIRECTIONS, Rook), (BISHOP_DIRECTIONS, Bishop)] {
+        for dir in directions {
+            let mut square = (pos.0 as i32 + dir.0, pos.1 as i32 + dir.1);
+            while on_board(square.0, square.1) {
+                if let Square::Full(piece) = board[square.0 as usize][square.1 as usize] {
+                    if (piece.typ == typ || piece.typ == Queen) && piece.side == side {
+                        attackers.push(Point(square.0 as usize, square.1 as usize));
+                    }
+                    break;
+                }
+                square.0 += dir.0;
+                square.1 += dir.1;
+            }
+        }
+    }
+
+    // Find knight, king, and pawn attackers
+
+    let pawn_row = match side {
+        White => -1,
+        Black => 1
+    };
+    for (moves, typ) in [(&KNIGHT_MOVES[..], Knight), (&KING_MOVES[..], King), (&[(pawn_row, -1), (pawn_row, 1)][..], Pawn)] {
+        for mov in moves {
+            let row = pos.0 as i32 + mov.0;
+            let col = pos.1 as i32 + mov.1;
+            if on_board(row, col) && board[row as usize][col as usize] == Square::full(side, typ) {
+                attackers.push(Point(row as usize, col as usize));
+            }
+        }
+    }
+
+    attackers
+}
+
 // Check if king can castle in specified direction given castling right
 
 pub fn can_castle(
@@ -372,23 +624,155 @@ pub fn can_castle(
     board: &[[Square; SIZE]; SIZE],
     king_pos: Point
 ) -> bool {
-    // Check across columns for pieces or checks
+    // Check rook is on initial square in case castle rights are out of sync
 
-    let cols = match dir {
-        CastleDirection::A => CASTLE_COLUMNS.0,
-        CastleDirection::H => CASTLE_COLUMNS.1
+    let rook_pos = match (side, dir) {
+        (White, CastleDirection::A) => INITIAL_POSITIONS.WHITE_ROOKS.0,
+        (White, CastleDirection::H) => INITIAL_POSITIONS.WHITE_ROOKS.1,
+        (Black, CastleDirection::A) => INITIAL_POSITIONS.BLACK_ROOKS.0,
+        (Black, CastleDirection::H) => INITIAL_POSITIONS.BLACK_ROOKS.1
     };
+    match board[rook_pos.0][rook_pos.1] {
+        Square::Full(piece) if piece.side == side && piece.typ == Rook => (),
+        _ => return false
+    }
 
-    for col in cols {
-        if col != king_pos.1 {
-            if let Square::Full(_) = board[king_pos.0][col] {
-                return false;
-            }
+    // Check for pieces between king and rook
+
+    let (empty_cols, safe_cols) = match dir {
+        CastleDirection::A => (CASTLE_EMPTY_COLUMNS.0, CASTLE_COLUMNS.0),
+        CastleDirection::H => (CASTLE_EMPTY_COLUMNS.1, CASTLE_COLUMNS.1)
+    };
+    for col in empty_cols {
+        if let Square::Full(_) = board[king_pos.0][col] {
+            return false;
         }
+    }
+
+    // Check for checks on squares king starts on and crosses
+
+    for col in safe_cols {
         if in_check(side, Point(king_pos.0, col), board) {
             return false;
         }
     }
 
     true
+}
+
+#[cfg(all(test, not(feature = "board-9x9")))]
+mod tests {
+    use super::*;
+    use crate::board::{ Game, PieceType };
+    use crate::move_input;
+
+    fn mov(input: &str) -> Move {
+        move_input::parse_coordinates(input).unwrap().0
+    }
+
+    // Sliding pieces on the a-file give check
+
+    #[test]
+    fn check_from_a_file() {
+        let game = Game::from_fen("r6k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert!(in_check(White, Point(0, 0), &game.board));
+
+        let game = Game::from_fen("7k/8/8/b7/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(in_check(White, Point(0, 4), &game.board));
+    }
+
+    // Pawns only attack toward the opposing side
+
+    #[test]
+    fn pawn_check_direction() {
+        let game = Game::from_fen("7K/6P1/8/8/8/8/8/k7 w - - 0 1").unwrap();
+        assert!(!in_check(White, Point(7, 7), &game.board));
+
+        let game = Game::from_fen("8/8/8/8/8/8/1p6/K6k w - - 0 1").unwrap();
+        assert!(in_check(White, Point(0, 0), &game.board));
+
+        let game = Game::from_fen("k7/1P6/8/8/8/8/8/7K b - - 0 1").unwrap();
+        assert!(in_check(Black, Point(7, 0), &game.board));
+    }
+
+    // Pawn on its start row can't double push onto an occupied square even when the square in between is clear
+
+    #[test]
+    fn double_push_blocked() {
+        let mut game = Game::from_fen("4k3/8/8/8/4n3/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(get_pawn_moves(White, 1, 4, &game.board, None), vec![Point(2, 4)]);
+        assert!(!game.is_legal_move(mov("e2e4")));
+
+        let mut game = Game::from_fen("4k3/4p3/8/4N3/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(get_pawn_moves(Black, 6, 4, &game.board, None), vec![Point(5, 4)]);
+        assert!(!game.is_legal_move(mov("e7e5")));
+    }
+
+    // Only a pawn beside the double moved pawn capturing onto the square behind it is en passant
+
+    #[test]
+    fn en_passant_capture() {
+        let game = Game::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+        assert!(is_en_passant(White, mov("d5e6"), &game.board, game.pawn_double_moved));
+        assert!(!is_en_passant(White, mov("d5c6"), &game.board, game.pawn_double_moved));
+        assert!(!is_en_passant(White, mov("d5e6"), &game.board, None));
+
+        let game = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        assert!(is_en_passant(Black, mov("d4e3"), &game.board, game.pawn_double_moved));
+        assert!(!is_en_passant(Black, mov("d4d3"), &game.board, game.pawn_double_moved));
+    }
+
+    // Castling with rights but no rook on the corner is rejected
+
+    #[test]
+    fn castle_without_rook() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w KQ - 0 1").unwrap();
+        assert!(!can_castle(White, CastleDirection::A, &game.board, Point(0, 4)));
+        assert!(!game.is_legal_move(mov("e1c1")));
+        assert!(game.is_legal_move(mov("e1g1")));
+
+        let move_data = move_input::parse_move("O-O-O").unwrap();
+        assert!(game.apply_player_move("O-O-O", move_data).is_err());
+    }
+
+    // Piece next to rook blocks queenside castling even though king doesn't cross it
+
+    #[test]
+    fn castle_blocked_on_b_file() {
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap();
+        assert!(!game.is_legal_move(mov("e1c1")));
+        assert!(game.is_legal_move(mov("e1g1")));
+
+        let mut game = Game::from_fen("rn2k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert!(!game.is_legal_move(mov("e8c8")));
+        assert!(game.is_legal_move(mov("e8g8")));
+    }
+
+    // Piece generators never target enemy king
+
+    #[test]
+    fn no_king_targets() {
+        let mut board = [[Square::Empty; SIZE]; SIZE];
+        board[0][0] = Square::full(Side::White, PieceType::King);
+        board[4][4] = Square::full(Side::Black, PieceType::King);
+        board[2][3] = Square::full(Side::White, PieceType::Knight);
+        board[3][3] = Square::full(Side::White, PieceType::Pawn);
+        board[4][0] = Square::full(Side::White, PieceType::Rook);
+        assert!(!get_knight_moves(Side::White, 2, 3, &board).contains(&Point(4, 4)));
+        assert!(!get_pawn_moves(Side::White, 3, 3, &board, None).contains(&Point(4, 4)));
+        assert!(!get_rook_moves(Side::White, 4, 0, &board).contains(&Point(4, 4)));
+    }
+
+    // King can't move next to enemy king
+
+    #[test]
+    fn king_adjacency() {
+        let mut game = Game::from_fen("8/8/8/3k4/8/3K4/8/8 w - - 0 1").unwrap();
+        let legal = game.legal_moves();
+        for input in ["d3c4", "d3d4", "d3e4"] {
+            assert!(!legal.contains(&(mov(input), None)), "{input}");
+        }
+        assert!(legal.contains(&(mov("d3c3"), None)));
+        assert_eq!(legal.len(), 5);
+    }
 }
\ No newline at end of file
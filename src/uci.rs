@@ -0,0 +1,134 @@
+// Imports
+
+use crate::board::Side::White;
+use crate::board::PieceType;
+use crate::board::Move;
+use crate::board::Game;
+use crate::move_input;
+use crate::engine;
+use crate::engine::book::Book;
+
+use std::io;
+use std::time::Instant;
+use rand_chacha::ChaCha8Rng;
+
+// UCI parameters
+
+const DEFAULT_DEPTH: u32 = 4;
+const MAX_DEPTH: u32 = 64;
+const BRANCHING_FACTOR: u128 = 4;
+
+// Run UCI protocol loop on standard input and output
+
+pub fn run_uci() {
+    let mut game = Game::new();
+    let book = Book::empty();
+    let mut rng = engine::create_rng(engine::get_seed());
+
+    for line in io::stdin().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match tokens.first() {
+            Some(&"uci") => {
+                println!("id name Bad Chess");
+                println!("id author MrEconomical");
+                println!("uciok");
+            },
+            Some(&"isready") => println!("readyok"),
+            Some(&"ucinewgame") => game = Game::new(),
+            Some(&"position") => match read_position(&tokens[1..]) {
+                Ok(position) => game = position,
+                Err(error) => println!("info string {error}")
+            },
+            Some(&"go") => {
+                let (mov, promotion) = match go(&mut game, &book, &tokens[1..], &mut rng) {
+                    Some(mov) => mov,
+                    None => {
+                        println!("bestmove 0000");
+                        continue;
+                    }
+                };
+                println!("bestmove {}", move_input::to_coordinates(mov, promotion));
+            },
+            Some(&"quit") => break,
+            _ => ()
+        }
+    }
+}
+
+// Read position from startpos or FEN followed by optional coordinate moves
+
+fn read_position(tokens: &[&str]) -> Result<Game, String> {
+    let moves_index = tokens.iter().position(|token| *token == "moves").unwrap_or(tokens.len());
+    let mut game = match tokens.first() {
+        Some(&"startpos") => Game::new(),
+        Some(&"fen") => Game::from_fen(&tokens[1..moves_index].join(" "))?,
+        _ => return Err(String::from("Expected 'startpos' or 'fen' in position command"))
+    };
+
+    for input in tokens.iter().skip(moves_index + 1) {
+        let (mov, promotion) = move_input::parse_coordinates(input)
+            .ok_or_else(|| format!("'{input}' isn't a valid coordinate move"))?;
+        if !game.legal_moves().contains(&(mov, promotion)) {
+            return Err(format!("'{input}' isn't a legal move in the position"));
+        }
+        game.make_move(mov, promotion)?;
+    }
+    Ok(game)
+}
+
+// Search position to depth or for move time and print search info for each depth
+
+fn go(
+    game: &mut Game,
+    book: &Book,
+    tokens: &[&str],
+    rng: &mut ChaCha8Rng
+) -> Option<(Move, Option<PieceType>)> {
+    // Get search limits
+
+    let get_value = |name: &str| -> Option<u64> {
+        let index = tokens.iter().position(|token| *token == name)?;
+        tokens.get(index + 1)?.parse().ok()
+    };
+    let movetime = get_value("movetime");
+    let max_depth = match (get_value("depth"), movetime) {
+        (Some(depth), _) => depth.clamp(1, MAX_DEPTH as u64) as u32,
+        (None, Some(_)) => MAX_DEPTH,
+        (None, None) => DEFAULT_DEPTH
+    };
+
+    // Search with increasing depth until next depth would likely exceed move time
+    // Move time is only checked between depths, so a single deep search can run past it
+
+    let start = Instant::now();
+    let mut best = None;
+    for depth in 1..=max_depth {
+        let (mov, stats) = engine::search(game, book, depth, rng);
+        let (mov, promotion) = match mov {
+            Some(mov) => mov,
+            None => break
+        };
+        best = Some((mov, promotion));
+
+        let score = if game.turn == White { stats.score } else { -stats.score };
+        let score = match engine::get_mate_moves(score) {
+            Some(moves) => format!("mate {moves}"),
+            None => format!("cp {score}")
+        };
+        let elapsed = start.elapsed().as_millis();
+        let pv = move_input::to_coordinates(mov, promotion);
+        println!("info depth {depth} score {score} nodes {} time {elapsed} pv {pv}", stats.nodes);
+
+        if let Some(movetime) = movetime {
+            if elapsed * BRANCHING_FACTOR >= movetime as u128 {
+                break;
+            }
+        }
+    }
+    best
+}
\ No newline at end of file
@@ -1,6 +1,15 @@
 // Imports
 
+use std::env;
+use std::io::{ self, IsTerminal };
 use std::process;
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+// Display settings
+
+static LETTER_PIECES: AtomicBool = AtomicBool::new(false);
+static SHOW_TRANSCRIPT: AtomicBool = AtomicBool::new(false);
+static SHOW_CAPTURES: AtomicBool = AtomicBool::new(false);
 
 // Clear screen with error handling
 
@@ -9,4 +18,75 @@ pub fn clear_screen() {
         eprintln!("Fatal error clearing terminal: {}", error);
         process::exit(1);
     }
+}
+
+// Disable colored output for flag, NO_COLOR convention, or non-terminal output
+
+pub fn configure_color(no_color: bool) {
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color || no_color_env || !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+// Set whether pieces are displayed as letters instead of glyphs
+
+pub fn set_letter_pieces(letters: bool) {
+    LETTER_PIECES.store(letters, Ordering::Relaxed);
+}
+
+// Check whether pieces are displayed as letters instead of glyphs
+
+pub fn letter_pieces() -> bool {
+    LETTER_PIECES.load(Ordering::Relaxed)
+}
+
+// Set whether move transcript is displayed below board
+
+pub fn set_show_transcript(transcript: bool) {
+    SHOW_TRANSCRIPT.store(transcript, Ordering::Relaxed);
+}
+
+// Check whether move transcript is displayed below board
+
+pub fn show_transcript() -> bool {
+    SHOW_TRANSCRIPT.load(Ordering::Relaxed)
+}
+
+// Set whether captured pieces are displayed below board
+
+pub fn set_show_captures(captures: bool) {
+    SHOW_CAPTURES.store(captures, Ordering::Relaxed);
+}
+
+// Check whether captured pieces are displayed below board
+
+pub fn show_captures() -> bool {
+    SHOW_CAPTURES.load(Ordering::Relaxed)
+}
+
+// Read trimmed line from standard input or none at end of input
+
+pub fn read_input() -> Result<Option<String>, String> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => Ok(None),
+        Ok(_) => Ok(Some(String::from(input.trim()))),
+        Err(_) => Err(String::from("Error reading input, please try again"))
+    }
+}
+
+// Check if input is a case-insensitive quit or exit command
+
+pub fn is_quit(input: &str) -> bool {
+    input.eq_ignore_ascii_case("quit") || input.eq_ignore_ascii_case("exit")
+}
+
+// Get terminal width in columns from COLUMNS environment variable if set and valid
+
+pub fn terminal_width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
 }
\ No newline at end of file
@@ -17,6 +17,7 @@ const ZOBRIST_SEED: u64 = 37811;
 
 // Random number parameters for Zobrist hashes
 
+#[derive(Clone)]
 #[derive(Debug)]
 pub struct Zobrist {
     pub piece_table: [[[u64; SIZE]; SIZE]; PIECE_TYPES],
@@ -27,10 +28,16 @@ pub struct Zobrist {
 }
 
 impl Zobrist {
-    // Generate pseudorandom numbers for hashing
+    // Generate pseudorandom numbers for hashing from default seed
 
     pub fn new() -> Zobrist {
-        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(ZOBRIST_SEED);
+        Zobrist::with_seed(ZOBRIST_SEED)
+    }
+
+    // Generate pseudorandom numbers for hashing from seed
+
+    pub fn with_seed(seed: u64) -> Zobrist {
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
 
         let mut piece_table = [[[0; SIZE]; SIZE]; PIECE_TYPES];
         #[allow(clippy::needless_range_loop)]
@@ -74,33 +81,21 @@ impl Zobrist {
         };
         self.piece_table[index][square.0][square.1]
     }
-}
 
-// Zobrist hash table mapping seen positions
+    // Get key for position
 
-#[derive(Debug)]
-pub struct ZobristTable<V> {
-    zobrist: Zobrist,
-    table: HashMap<u64, V, BuildNoHashHasher<u64>>,
-    key: u64
-}
-
-impl<V> ZobristTable<V> {
-    // Create new table with start capacity and key
-
-    pub fn new(
-        capacity: usize,
+    pub fn get_key(
+        &self,
         side: Side,
         board: &[[Square; SIZE]; SIZE],
         castle_rights: CastleRights,
         pawn_double_moved: Option<Point>
-    ) -> ZobristTable<V> {
-        // Intialize Zobrist parameters
+    ) -> u64 {
+        // Get side to move key
 
-        let zobrist = Zobrist::new();
         let mut zobrist_key = match side {
             White => 0,
-            Black => zobrist.black_turn
+            Black => self.black_turn
         };
 
         // Update hash with piece positions
@@ -109,7 +104,7 @@ impl<V> ZobristTable<V> {
         for r in 0..SIZE {
             for c in 0..SIZE {
                 if let Square::Full(piece) = board[r][c] {
-                    zobrist_key ^= zobrist.get_piece_key(piece, Point(r, c));
+                    zobrist_key ^= self.get_piece_key(piece, Point(r, c));
                 }
             }
         }
@@ -127,7 +122,7 @@ impl<V> ZobristTable<V> {
                     }
                 }
             }
-            if en_passant == None && point.1 < SIZE - 1 {
+            if en_passant.is_none() && point.1 < SIZE - 1 {
                 if let Square::Full(piece) = board[point.0][point.1 + 1] {
                     if piece.typ == Pawn &&
                        ((piece.side == White && board[point.0 + 1][point.1] == Square::Empty) ||
@@ -139,30 +134,96 @@ impl<V> ZobristTable<V> {
         }
 
         if let Some(col) = en_passant {
-            zobrist_key ^= zobrist.en_passant[col];
+            zobrist_key ^= self.en_passant[col];
         }
 
         // Update hash with castle rights
 
         if castle_rights.white.0 {
-            zobrist_key ^= zobrist.white_castle_rights.0;
+            zobrist_key ^= self.white_castle_rights.0;
         }
         if castle_rights.white.1 {
-            zobrist_key ^= zobrist.white_castle_rights.1;
+            zobrist_key ^= self.white_castle_rights.1;
         }
         if castle_rights.black.0 {
-            zobrist_key ^= zobrist.black_castle_rights.0;
+            zobrist_key ^= self.black_castle_rights.0;
         }
         if castle_rights.black.1 {
-            zobrist_key ^= zobrist.black_castle_rights.1;
+            zobrist_key ^= self.black_castle_rights.1;
         }
 
+        zobrist_key
+    }
+}
+
+impl Default for Zobrist {
+    fn default() -> Zobrist {
+        Zobrist::new()
+    }
+}
+
+// Zobrist hash table mapping seen positions
+
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct ZobristTable<V> {
+    zobrist: Zobrist,
+    table: HashMap<u64, V, BuildNoHashHasher<u64>>,
+    key: u64
+}
+
+impl<V> ZobristTable<V> {
+    // Create new table with start capacity, key, and optional seed
+
+    pub fn new(
+        capacity: usize,
+        side: Side,
+        board: &[[Square; SIZE]; SIZE],
+        castle_rights: CastleRights,
+        pawn_double_moved: Option<Point>,
+        seed: Option<u64>
+    ) -> ZobristTable<V> {
+        // Intialize Zobrist parameters
+
+        let zobrist = Zobrist::with_seed(seed.unwrap_or(ZOBRIST_SEED));
+        let key = zobrist.get_key(side, board, castle_rights, pawn_double_moved);
+
         // Initialized table
 
         ZobristTable {
             zobrist,
             table: HashMap::with_capacity_and_hasher(capacity, BuildNoHashHasher::default()),
-            key: zobrist_key
+            key
         }
     }
+
+    // Update key to new position
+
+    pub fn update_key(
+        &mut self,
+        side: Side,
+        board: &[[Square; SIZE]; SIZE],
+        castle_rights: CastleRights,
+        pawn_double_moved: Option<Point>
+    ) {
+        self.key = self.zobrist.get_key(side, board, castle_rights, pawn_double_moved);
+    }
+
+    // Get key for current position
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    // Get value stored for current position
+
+    pub fn get(&self) -> Option<&V> {
+        self.table.get(&self.key)
+    }
+
+    // Store value for current position
+
+    pub fn insert(&mut self, value: V) {
+        self.table.insert(self.key, value);
+    }
 }
\ No newline at end of file